@@ -0,0 +1,142 @@
+//! This module invokes the execution of the 'ADD' command.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{Add, Defaults};
+use crate::crates::Crate;
+use crate::fs::{self, Dependency};
+use crate::template::Template;
+use crate::workspace::{self, Metadata, Workspace};
+
+pub fn run(cfg: &Add) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let root_dir = ws.root_path.to_string_lossy();
+
+    if ws.package(&cfg.crate_name).is_some() {
+        println!("{} is already a member of the workspace", cfg.crate_name);
+        std::process::exit(1);
+    }
+
+    // The defaults of the workspace take precedence over the ones of the user.
+    let mut defaults = ws.settings.defaults.clone();
+    defaults.template_dir = defaults
+        .template_dir
+        .map(|d| ws.root_path.join(d).to_string_lossy().into_owned());
+    let defaults = defaults.or(Defaults::load_user_or_handle_error());
+
+    let template_dir = cfg
+        .template_dir
+        .as_ref()
+        .or(defaults.template_dir.as_ref())
+        .map(PathBuf::from);
+    let template =
+        cfg.template
+            .as_ref()
+            .map(|name| match Template::load(name, template_dir.as_deref()) {
+                Ok(t) => t,
+                Err(e) => {
+                    println!("{}", e);
+                    std::process::exit(1);
+                }
+            });
+
+    let kind = template.as_ref().map(Template::crate_kind).or(cfg.kind);
+    let mut new_crate = Crate::new_from_user_input(
+        false,
+        ws.root_package().is_some(),
+        false,
+        Some(&cfg.crate_name),
+        cfg.directory_name.as_deref(),
+        kind,
+        &defaults,
+    );
+    new_crate.template = cfg.template.clone();
+    new_crate.derive = cfg.derive;
+    new_crate.targets = cfg.targets();
+    if !cfg.derive && kind.is_none() {
+        new_crate.derive_from_user_input(false);
+    }
+    if new_crate.derive && !new_crate.can_have_derive_crate() {
+        println!(
+            "{} has a derive crate, but is not a library crate",
+            new_crate.crate_name
+        );
+        std::process::exit(1);
+    }
+    if let Err(e) = new_crate.validate_targets(&new_crate.crate_name) {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+
+    let mut derive_crate = new_crate.derive.then(|| new_crate.derive_crate());
+    if let Some(d) = derive_crate
+        .as_ref()
+        .filter(|d| ws.package(&d.crate_name).is_some())
+    {
+        println!("{} is already a member of the workspace", d.crate_name);
+        std::process::exit(1);
+    }
+    for c in derive_crate
+        .iter_mut()
+        .chain(std::iter::once(&mut new_crate))
+    {
+        if let Err(e) = c.load_template(template_dir.as_deref()) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut manifest = fs::load_manifest_or_handle_error(&root_dir);
+    let members = manifest.workspace_members();
+    for c in derive_crate.iter().chain(std::iter::once(&new_crate)) {
+        let directory = fs::normalize_path(Path::new(&c.directory_name));
+        // A glob pattern like `crates/*` already includes the new crate.
+        if ws.packages.iter().any(|p| p.directory == directory)
+            || (!workspace::matches_member_pattern(&members, &directory)
+                && !manifest.add_workspace_member(&c.directory_name))
+        {
+            println!("{} is already a member of the workspace", c.directory_name);
+            std::process::exit(1);
+        }
+    }
+
+    // New crates inherit the keys of [workspace.package], if there is one.
+    let metadata = match ws.metadata.inherit {
+        true => ws.metadata.clone(),
+        false => {
+            let mut metadata = Metadata::default();
+            metadata.apply_defaults(&defaults);
+            metadata
+        }
+    };
+    // Templates refer to the workspace by the name of the root crate or of its directory.
+    let project_name = match ws.root_package() {
+        Some(p) => p.name.clone(),
+        None => fs::absolute_path(&ws.root_path)
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_default(),
+    };
+
+    // The new crate depends on its derive crate and re-exports its macros.
+    let mut deps = Vec::new();
+    if let Some(d) = &derive_crate {
+        d.write_to_disk(&root_dir, &project_name, &Vec::new(), &metadata);
+        let path = fs::relative_path(
+            Path::new(&new_crate.directory_name),
+            Path::new(&d.directory_name),
+        );
+        deps.push(Dependency::path(&d.crate_name, &path.to_string_lossy()));
+    }
+    new_crate.write_to_disk(&root_dir, &project_name, &deps, &metadata);
+
+    if new_crate.as_dependency {
+        manifest.set_path_dependency(
+            "dependencies",
+            &new_crate.crate_name,
+            &new_crate.directory_name,
+        );
+    }
+
+    fs::save_manifest_or_handle_error(&manifest);
+}
@@ -0,0 +1,140 @@
+//! Handles blueprints, which describe the shape of a workspace
+//! (root crate, members and shared metadata) in a toml or json file.
+
+use std::path::Path;
+
+use crate::config::BlueprintFormat;
+use crate::fs::{self, IOError};
+use crate::workspace::Workspace;
+
+/// Determines the format of a blueprint file by its extension.
+/// Files without a `.json` extension are treated as toml.
+pub fn format_of(path: &Path) -> BlueprintFormat {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => BlueprintFormat::Json,
+        _ => BlueprintFormat::Toml,
+    }
+}
+
+/// Reads a blueprint file. The returned [Workspace] has no project
+/// and directory name, since those are not part of a blueprint.
+pub fn read(path: &Path) -> Result<Workspace, IOError> {
+    let mut ws: Workspace = match format_of(path) {
+        BlueprintFormat::Toml => fs::read_toml_file(path)?,
+        BlueprintFormat::Json => fs::read_json_file(path)?,
+    };
+
+    if let Some(root) = &mut ws.root_crate {
+        root.is_root = true;
+        root.directory_name = String::from(".");
+    }
+
+    for c in &mut ws.crates {
+        c.is_root = false;
+        if c.directory_name.is_empty() {
+            c.directory_name = c.crate_name.clone();
+        }
+    }
+
+    Ok(ws)
+}
+
+/// Reads a blueprint file by using [read], but errors will be handled by
+/// displaying an error message and exiting the process with code 1.
+pub fn read_or_handle_error(filename: &str) -> Workspace {
+    match read(Path::new(filename)) {
+        Ok(ws) => ws,
+        Err(e) => {
+            println!("Reading blueprint {} failed with error: {}", filename, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Serializes a workspace into a blueprint.
+pub fn to_string(ws: &Workspace, format: BlueprintFormat) -> Result<String, IOError> {
+    match format {
+        BlueprintFormat::Toml => match toml::to_string(ws) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(IOError::TomlSerError(e)),
+        },
+        BlueprintFormat::Json => match serde_json::to_string_pretty(ws) {
+            Ok(s) => Ok(s + "\n"),
+            Err(e) => Err(IOError::JsonError(e)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CrateKind;
+    use crate::fs::context_setup::TestFiles;
+    use test_context::test_context;
+
+    const BLUEPRINT: &str = "\
+[metadata]
+version = \"0.3.0\"
+license = \"MIT\"
+
+[root]
+kind = \"bin\"
+
+[[members]]
+name = \"core\"
+directory = \"crates/core\"
+as_dependency = true
+
+[[members]]
+name = \"model\"
+derive = true
+
+[[members]]
+name = \"cli\"
+template = \"bin-lib\"
+dependencies = [\"core\"]
+";
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_read_blueprint(ctx: &mut TestFiles) {
+        let filename = format!("{}/blueprint.toml", ctx.name);
+        fs::write_file(Path::new(&filename), &String::from(BLUEPRINT), false).unwrap();
+
+        let ws = read(Path::new(&filename)).unwrap();
+
+        assert_eq!(ws.metadata.version, Some(String::from("0.3.0")));
+        assert_eq!(ws.metadata.license, Some(String::from("MIT")));
+
+        let root = ws.root_crate.as_ref().unwrap();
+        assert!(root.is_root);
+        assert_eq!(root.kind, CrateKind::Bin);
+
+        assert_eq!(ws.crates.len(), 3);
+        assert_eq!(ws.crates[0].directory_name, "crates/core");
+        assert!(ws.crates[0].as_dependency);
+        assert_eq!(ws.crates[0].kind, CrateKind::Lib);
+        assert!(ws.crates[1].derive);
+        assert_eq!(ws.crates[2].directory_name, "cli");
+        assert_eq!(ws.crates[2].dependencies, vec![String::from("core")]);
+        assert_eq!(ws.crates[2].template.as_deref(), Some("bin-lib"));
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_blueprint_round_trip(ctx: &mut TestFiles) {
+        let filename = format!("{}/blueprint.toml", ctx.name);
+        fs::write_file(Path::new(&filename), &String::from(BLUEPRINT), false).unwrap();
+        let ws = read(Path::new(&filename)).unwrap();
+
+        let json_filename = format!("{}/blueprint.json", ctx.name);
+        let json = to_string(&ws, BlueprintFormat::Json).unwrap();
+        fs::write_file(Path::new(&json_filename), &json, false).unwrap();
+        let from_json = read(Path::new(&json_filename)).unwrap();
+
+        assert_eq!(
+            to_string(&ws, BlueprintFormat::Toml).unwrap(),
+            to_string(&from_json, BlueprintFormat::Toml).unwrap()
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! This module invokes the execution of the 'CHANGED' command.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::{Changed, ChangedFormat};
+use crate::workspace::Workspace;
+
+/// An entry of the json output of the 'changed' command.
+#[derive(Serialize)]
+struct ChangedEntry<'a> {
+    name: &'a str,
+    directory: String,
+
+    /// Whether the crate was changed itself, or only depends on a changed crate.
+    changed: bool,
+}
+
+pub fn run(cfg: &Changed) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let affected = match ws.affected_packages(&cfg.since) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("Finding changed crates failed with error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cfg.format.unwrap_or(ChangedFormat::Plain) {
+        ChangedFormat::Plain => {
+            for (package, _) in affected {
+                println!("{}", package.name);
+            }
+        }
+        ChangedFormat::Json => {
+            let entries: Vec<ChangedEntry> = affected
+                .into_iter()
+                .map(|(p, changed)| ChangedEntry {
+                    name: &p.name,
+                    directory: p.directory.to_string_lossy().into_owned(),
+                    changed,
+                })
+                .collect();
+
+            match serde_json::to_string_pretty(&entries) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    println!("Creating the json output failed with error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
@@ -2,9 +2,37 @@
 
 use clap::{AppSettings, Parser, Subcommand};
 
+pub mod add;
+pub mod changed;
+pub mod defaults;
+pub mod dep;
+pub mod deps;
+pub mod exec;
+pub mod export_blueprint;
+pub mod list;
+pub mod mv;
 pub mod new;
+pub mod publish;
+pub mod remove;
+pub mod rename;
+pub mod targets;
+pub mod version;
 
+pub use add::*;
+pub use changed::*;
+pub use defaults::*;
+pub use dep::*;
+pub use deps::*;
+pub use exec::*;
+pub use export_blueprint::*;
+pub use list::*;
+pub use mv::*;
 pub use new::*;
+pub use publish::*;
+pub use remove::*;
+pub use rename::*;
+pub use targets::*;
+pub use version::*;
 
 /// This is the main configuration struct.
 ///
@@ -25,4 +53,41 @@ pub struct Config {
 pub enum Commands {
     ///Create new workspace
     New(New),
+
+    ///Add a new member crate to an existing workspace
+    Add(Add),
+
+    ///Remove a member crate from a workspace
+    Remove(Remove),
+
+    ///Write a blueprint describing the current workspace
+    ExportBlueprint(ExportBlueprint),
+
+    ///List all member crates of a workspace
+    List(List),
+
+    ///Run a command in every member crate
+    #[clap(alias = "foreach")]
+    Exec(Exec),
+
+    ///Publish all crates of a workspace in the order of their dependencies
+    Publish(Publish),
+
+    ///Change the versions of the crates of a workspace
+    Version(Version),
+
+    ///List the crates affected by changes since a git reference
+    Changed(Changed),
+
+    ///Rename a crate and update everything that refers to it
+    Rename(Rename),
+
+    ///Move a crate to a different directory
+    Mv(Mv),
+
+    ///Manage the dependencies shared by the crates of a workspace
+    Deps(Deps),
+
+    ///Add dependencies to a single crate of a workspace
+    Dep(Dep),
 }
@@ -0,0 +1,67 @@
+//! Subcommand 'add'
+//!
+
+use clap::Args;
+
+use super::{CrateKind, TargetSpec, Targets};
+
+/// Stores futher arguments for the sub-command 'add'
+#[derive(Args)]
+pub struct Add {
+    /// Name of the new crate
+    #[clap(value_parser)]
+    pub crate_name: String,
+
+    /// Name of directory the crate should be created in
+    #[clap(value_parser)]
+    pub directory_name: Option<String>,
+
+    /// Kind of the crate
+    #[clap(long, value_enum, conflicts_with = "template")]
+    pub kind: Option<CrateKind>,
+
+    /// Template the files of the crate are created from, like `bin-lib` or `proc-macro`
+    #[clap(long, value_parser, value_name = "NAME")]
+    pub template: Option<String>,
+
+    /// Also create the proc-macro crate `<CRATE_NAME>-derive` and re-export its macros
+    #[clap(long, action)]
+    pub derive: bool,
+
+    /// Directory containing user templates, instead of the one stored in the root Cargo.toml
+    #[clap(long, value_parser, value_name = "DIR")]
+    pub template_dir: Option<String>,
+
+    /// Give a binary crate a library target as well
+    #[clap(long, action)]
+    pub lib: bool,
+
+    /// Binary target, put into `src/bin` unless a path is given (can be used multiple times)
+    #[clap(long = "bin", value_parser, value_name = "NAME[=PATH]")]
+    pub bins: Vec<TargetSpec>,
+
+    /// Example target (can be used multiple times)
+    #[clap(long = "example", value_parser, value_name = "NAME[=PATH]")]
+    pub examples: Vec<TargetSpec>,
+
+    /// Integration test target (can be used multiple times)
+    #[clap(long = "test", value_parser, value_name = "NAME[=PATH]")]
+    pub tests: Vec<TargetSpec>,
+
+    /// Benchmark target (can be used multiple times)
+    #[clap(long = "bench", value_parser, value_name = "NAME[=PATH]")]
+    pub benches: Vec<TargetSpec>,
+}
+
+impl Add {
+    /// Returns the targets given in addition to the ones of the template.
+    pub fn targets(&self) -> Targets {
+        Targets {
+            lib: self.lib,
+            bins: self.bins.clone(),
+            examples: self.examples.clone(),
+            tests: self.tests.clone(),
+            benches: self.benches.clone(),
+        }
+    }
+}
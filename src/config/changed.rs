@@ -0,0 +1,24 @@
+//! Subcommand 'changed'
+//!
+
+use clap::{Args, ValueEnum};
+
+/// Stores futher arguments for the sub-command 'changed'
+#[derive(Args)]
+pub struct Changed {
+    /// Git reference the changes are compared to, like `origin/main`
+    #[clap(long, value_parser, value_name = "REF")]
+    pub since: String,
+
+    /// Output format. Defaults to one crate name per line
+    #[clap(long, value_enum)]
+    pub format: Option<ChangedFormat>,
+}
+
+/// Possible output formats of the 'changed' command.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum ChangedFormat {
+    Plain,
+    Json,
+}
@@ -0,0 +1,176 @@
+//! Defaults for new workspaces and crates
+//!
+//! The defaults are read from the user configuration file
+//! `~/.config/cargo-ws-manage/config.toml` (or `$XDG_CONFIG_HOME/cargo-ws-manage/config.toml`)
+//! and from the `[workspace.metadata.ws-manage]` section of the root Cargo.toml of a workspace.
+//! Both use the same keys:
+//! ```toml
+//! edition = "2021"
+//! version = "0.1.0"
+//! license = "MIT OR Apache-2.0"
+//! authors = ["Jane Doe <jane@example.com>"]
+//! members-dir = "crates"
+//! glob-members = true
+//! crate-kind = "lib"
+//! template-dir = "templates"
+//! ```
+//! For every setting, the first of these sources that sets it is used:
+//!
+//! 1. the flags given on the command line, like `--edition`, `--members-dir`, `--glob-members`,
+//!    `--template-dir`, `--template` or the `lib`/`bin` part of `--member`,
+//!    or the blueprint given to `new --from`, which cannot be combined with these flags,
+//! 2. the `[workspace.metadata.ws-manage]` section, when a crate is added to a workspace,
+//! 3. the user configuration file,
+//! 4. the built-in defaults: edition 2021, version 0.1.0 and library crates.
+//!
+//! The crate kind is `lib`, `bin` or `proc-macro`.
+//!
+//! A relative `template-dir` is relative to the root of the workspace in the
+//! root Cargo.toml and relative to the configuration file in the user configuration.
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Defaults for new workspaces and crates, see the [module documentation](self).
+#[derive(Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Defaults {
+    pub edition: Option<String>,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub authors: Option<Vec<String>>,
+
+    /// Directory new member crates are created in by default, like `crates`.
+    pub members_dir: Option<String>,
+
+    /// Whether `members` of a new workspace is written as a glob pattern like `crates/*`
+    /// when all member crates are in the same directory.
+    pub glob_members: Option<bool>,
+
+    /// Kind of new member crates, if it is neither given nor implied by a template.
+    pub crate_kind: Option<CrateKind>,
+
+    /// Directory containing user templates for new crates.
+    pub template_dir: Option<String>,
+}
+
+/// Possible kinds of new crates, which decide the built-in template they are created from.
+#[derive(Serialize, Deserialize, ValueEnum, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum CrateKind {
+    #[default]
+    Lib,
+    Bin,
+    ProcMacro,
+}
+
+impl CrateKind {
+    /// Returns the name of the kind, which is also the name of its built-in template.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrateKind::Lib => "lib",
+            CrateKind::Bin => "bin",
+            CrateKind::ProcMacro => "proc-macro",
+        }
+    }
+
+    /// Whether other crates can depend on crates of this kind.
+    pub fn is_lib(&self) -> bool {
+        *self != CrateKind::Bin
+    }
+}
+
+impl Defaults {
+    /// Keeps all settings of `self` and takes the other ones from `fallback`,
+    /// which has a lower precedence.
+    pub fn or(self, fallback: Defaults) -> Defaults {
+        Defaults {
+            edition: self.edition.or(fallback.edition),
+            version: self.version.or(fallback.version),
+            license: self.license.or(fallback.license),
+            authors: self.authors.or(fallback.authors),
+            members_dir: self.members_dir.or(fallback.members_dir),
+            glob_members: self.glob_members.or(fallback.glob_members),
+            crate_kind: self.crate_kind.or(fallback.crate_kind),
+            template_dir: self.template_dir.or(fallback.template_dir),
+        }
+    }
+
+    /// Returns the path of the user configuration file, if the home directory is known.
+    pub fn user_config_path() -> Option<PathBuf> {
+        let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(config_home.join("cargo-ws-manage").join("config.toml"))
+    }
+
+    /// Reads the user configuration file. If it does not exist, no defaults are set.
+    pub fn load_user() -> Result<Defaults, String> {
+        match Defaults::user_config_path() {
+            Some(path) if path.is_file() => Defaults::from_file(&path),
+            _ => Ok(Defaults::default()),
+        }
+    }
+
+    /// Reads the user configuration file by using [Defaults::load_user], but errors will be
+    /// handled by displaying an error message and exiting the process with code 1.
+    pub fn load_user_or_handle_error() -> Defaults {
+        match Defaults::load_user() {
+            Ok(d) => d,
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Reads the defaults from a configuration file. A relative template
+    /// directory is resolved against the directory of the file.
+    fn from_file(path: &Path) -> Result<Defaults, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Reading {} failed with error: {}", path.display(), e))?;
+        let mut defaults: Defaults = toml::from_str(&content)
+            .map_err(|e| format!("Parsing {} failed with error: {}", path.display(), e))?;
+
+        if let (Some(dir), Some(parent)) = (&defaults.template_dir, path.parent()) {
+            defaults.template_dir = Some(parent.join(dir).to_string_lossy().into_owned());
+        }
+
+        Ok(defaults)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        let project = Defaults {
+            edition: Some(String::from("2018")),
+            crate_kind: Some(CrateKind::Bin),
+            ..Defaults::default()
+        };
+        let user: Defaults = toml::from_str(
+            "edition = \"2021\"\nlicense = \"MIT\"\nauthors = [\"Jane\"]\ncrate-kind = \"lib\"\nglob-members = true\n",
+        )
+        .unwrap();
+
+        let defaults = project.or(user);
+        assert_eq!(defaults.edition.as_deref(), Some("2018"));
+        assert_eq!(defaults.license.as_deref(), Some("MIT"));
+        assert_eq!(defaults.authors, Some(vec![String::from("Jane")]));
+        assert_eq!(defaults.crate_kind, Some(CrateKind::Bin));
+        assert_eq!(defaults.glob_members, Some(true));
+        assert_eq!(defaults.version, None);
+
+        let proc_macro: Defaults = toml::from_str("crate-kind = \"proc-macro\"").unwrap();
+        assert_eq!(proc_macro.crate_kind, Some(CrateKind::ProcMacro));
+        assert!(toml::from_str::<Defaults>("crate-kind = \"dylib\"").is_err());
+    }
+}
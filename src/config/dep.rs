@@ -0,0 +1,101 @@
+//! Subcommand 'dep'
+//!
+
+use clap::{Args, Subcommand};
+
+/// Stores futher arguments for the sub-command 'dep'
+#[derive(Args)]
+pub struct Dep {
+    #[clap(subcommand)]
+    pub command: DepCommands,
+}
+
+/// All sub-commands of 'dep'.
+#[derive(Subcommand)]
+pub enum DepCommands {
+    ///Add a dependency from crates.io, a path, a git repository or another registry to one crate
+    Add(DepAdd),
+}
+
+/// Stores futher arguments for the sub-command 'dep add'
+#[derive(Args)]
+pub struct DepAdd {
+    /// Name of the crate that gets the dependency
+    #[clap(value_parser, value_name = "CRATE")]
+    pub crate_name: String,
+
+    /// The dependency, optionally with a version requirement like `serde@1.0`.
+    /// Without a requirement, path or git repository, it is inherited from [workspace.dependencies]
+    #[clap(value_parser, value_name = "DEP[@VERSION]")]
+    pub dependency: String,
+
+    /// Directory of the dependency, relative to the current directory
+    #[clap(long, value_parser, value_name = "DIR", conflicts_with_all = &["git", "registry"])]
+    pub path: Option<String>,
+
+    /// URL of the git repository of the dependency
+    #[clap(long, value_parser, value_name = "URL", conflicts_with = "registry")]
+    pub git: Option<String>,
+
+    /// Branch of the git repository
+    #[clap(long, value_parser, requires = "git", conflicts_with_all = &["tag", "rev"])]
+    pub branch: Option<String>,
+
+    /// Tag of the git repository
+    #[clap(long, value_parser, requires = "git", conflicts_with = "rev")]
+    pub tag: Option<String>,
+
+    /// Revision of the git repository
+    #[clap(long, value_parser, requires = "git")]
+    pub rev: Option<String>,
+
+    /// Name of the registry the dependency is taken from instead of crates.io
+    #[clap(long, value_parser)]
+    pub registry: Option<String>,
+
+    /// Name the crate uses for the dependency, if it differs from the name of the package
+    #[clap(long, value_parser, value_name = "NAME")]
+    pub rename: Option<String>,
+
+    /// Features of the dependency the crate enables
+    #[clap(long, value_parser, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Disable the default features of the dependency
+    #[clap(long, action)]
+    pub no_default_features: bool,
+
+    /// Make the dependency optional
+    #[clap(long, action)]
+    pub optional: bool,
+
+    #[clap(flatten)]
+    pub table: DependencyTableArgs,
+
+    /// Only use the dependency on a target like `cfg(unix)` or `x86_64-pc-windows-gnu`
+    #[clap(long, value_parser, value_name = "TARGET")]
+    pub target: Option<String>,
+}
+
+/// Stores the arguments that select the dependency table of 'dep add' and 'deps add'
+#[derive(Args)]
+pub struct DependencyTableArgs {
+    /// Add the dependency to [dev-dependencies]
+    #[clap(long, action, conflicts_with = "build")]
+    pub dev: bool,
+
+    /// Add the dependency to [build-dependencies]
+    #[clap(long, action)]
+    pub build: bool,
+}
+
+impl DependencyTableArgs {
+    /// Name of the dependency table in the Cargo.toml of a crate.
+    pub fn name(&self) -> &'static str {
+        match (self.dev, self.build) {
+            (true, _) => "dev-dependencies",
+            (_, true) => "build-dependencies",
+            _ => "dependencies",
+        }
+    }
+}
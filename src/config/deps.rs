@@ -0,0 +1,66 @@
+//! Subcommand 'deps'
+//!
+
+use clap::{Args, Subcommand};
+
+use super::DependencyTableArgs;
+
+/// Stores futher arguments for the sub-command 'deps'
+#[derive(Args)]
+pub struct Deps {
+    #[clap(subcommand)]
+    pub command: DepsCommands,
+}
+
+/// All sub-commands of 'deps'.
+#[derive(Subcommand)]
+pub enum DepsCommands {
+    ///Move dependencies used by several crates into [workspace.dependencies]
+    Hoist(DepsHoist),
+
+    ///Add a dependency to [workspace.dependencies] and to some crates
+    Add(DepsAdd),
+
+    ///Report dependencies whose requirements or locked versions differ between the crates
+    Check(DepsCheck),
+}
+
+/// Stores futher arguments for the sub-command 'deps hoist'
+#[derive(Args)]
+pub struct DepsHoist {
+    /// Only print the dependencies that would be moved without changing anything
+    #[clap(long, action)]
+    pub dry_run: bool,
+}
+
+/// Stores futher arguments for the sub-command 'deps add'
+#[derive(Args)]
+pub struct DepsAdd {
+    /// The dependency, optionally with a version requirement like `serde@1.0`.
+    /// The requirement can be left out if the dependency is already in [workspace.dependencies]
+    #[clap(value_parser, value_name = "DEP[@VERSION]")]
+    pub dependency: String,
+
+    /// Crates that should use the dependency
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "CRATES")]
+    pub members: Vec<String>,
+
+    /// Features of the dependency the crates enable
+    #[clap(long, value_parser, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Make the dependency optional in the crates
+    #[clap(long, action)]
+    pub optional: bool,
+
+    #[clap(flatten)]
+    pub table: DependencyTableArgs,
+}
+
+/// Stores futher arguments for the sub-command 'deps check'
+#[derive(Args)]
+pub struct DepsCheck {
+    /// Change all compatible requirements of a dependency to the highest one
+    #[clap(long, action)]
+    pub fix: bool,
+}
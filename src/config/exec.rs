@@ -0,0 +1,30 @@
+//! Subcommand 'exec'
+//!
+
+use clap::Args;
+
+/// Stores futher arguments for the sub-command 'exec'
+#[derive(Args)]
+pub struct Exec {
+    /// Only run the command in crates whose name matches this glob pattern
+    #[clap(long, value_parser, value_name = "PATTERN")]
+    pub filter: Option<String>,
+
+    /// Only run the command in crates with changes since this git reference
+    /// and in the crates depending on them
+    #[clap(long, value_parser, value_name = "REF")]
+    pub since: Option<String>,
+
+    /// Number of crates the command is run in at the same time
+    #[clap(short, long, value_parser = clap::value_parser!(u16).range(1..), default_value_t = 1)]
+    pub parallel: u16,
+
+    /// Run the command in the order of the dependencies between the crates.
+    /// Crates that do not depend on each other can still run in parallel
+    #[clap(long, action)]
+    pub topo: bool,
+
+    /// The command to run, for example `cargo clippy`
+    #[clap(value_parser, last = true, required = true)]
+    pub command: Vec<String>,
+}
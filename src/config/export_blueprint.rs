@@ -0,0 +1,24 @@
+//! Subcommand 'export-blueprint'
+//!
+
+use clap::{Args, ValueEnum};
+
+/// Stores futher arguments for the sub-command 'export-blueprint'
+#[derive(Args)]
+pub struct ExportBlueprint {
+    /// File the blueprint should be written to. If omitted, it is printed
+    #[clap(value_parser)]
+    pub file: Option<String>,
+
+    /// Format of the blueprint. Defaults to the extension of the file or toml
+    #[clap(long, value_enum)]
+    pub format: Option<BlueprintFormat>,
+}
+
+/// Possible formats of a blueprint file.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum BlueprintFormat {
+    Toml,
+    Json,
+}
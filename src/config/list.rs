@@ -0,0 +1,21 @@
+//! Subcommand 'list'
+//!
+
+use clap::{Args, ValueEnum};
+
+/// Stores futher arguments for the sub-command 'list'
+#[derive(Args)]
+pub struct List {
+    /// Output format. Defaults to a human readable table
+    #[clap(long, value_enum)]
+    pub format: Option<ListFormat>,
+}
+
+/// Possible output formats of the 'list' command.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum ListFormat {
+    Human,
+    Json,
+    Tsv,
+}
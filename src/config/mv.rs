@@ -0,0 +1,24 @@
+//! Subcommand 'mv'
+//!
+
+use clap::Args;
+
+/// Stores futher arguments for the sub-command 'mv'
+#[derive(Args)]
+pub struct Mv {
+    /// Name of the crate that should be moved
+    #[clap(value_parser)]
+    pub crate_name: String,
+
+    /// New directory of the crate, relative to the current directory
+    #[clap(value_parser)]
+    pub new_dir: String,
+
+    /// Only print the changes without applying them
+    #[clap(long, action)]
+    pub dry_run: bool,
+
+    /// Apply the changes without asking
+    #[clap(short, long, action)]
+    pub yes: bool,
+}
@@ -1,7 +1,11 @@
 //! Subcommand 'new'
 //!
 
-use clap::Args;
+use std::str::FromStr;
+
+use clap::{Args, ValueEnum};
+
+use super::{CrateKind, Defaults, Targets};
 
 /// Stores futher arguments for the sub-command 'new'
 #[derive(Args)]
@@ -13,4 +17,239 @@ pub struct New {
     /// Name of directory the workspace should be created in
     #[clap(value_parser)]
     pub directory_name: Option<String>,
+
+    /// Kind of the root crate
+    #[clap(long, value_enum)]
+    pub root: Option<RootKind>,
+
+    /// Member crate, specified as name[:dir][:lib|bin|proc-macro][:dep][:derive][:uses=a,b][:template=NAME]
+    /// [:bins=a,b][:examples=a,b][:tests=a,b][:benches=a,b] (can be used multiple times). `dep` makes it
+    /// a dependency of the root crate, `derive` adds a proc-macro crate `name-derive` whose macros it
+    /// re-exports, `uses` lists the member crates it depends on and `template` selects the template its
+    /// files are created from. `lib:bin` creates a binary crate with a library, and the other targets
+    /// are given as name[=path]
+    #[clap(long = "member", value_parser, value_name = "SPEC")]
+    pub members: Vec<MemberSpec>,
+
+    /// Blueprint file (toml or json) describing the workspace
+    #[clap(long, value_parser, value_name = "FILE", conflicts_with_all = &["root", "members", "members-dir", "glob-members", "workspace-package", "edition", "license", "authors"])]
+    pub from: Option<String>,
+
+    /// Directory the member crates are created in by default, like `crates`
+    #[clap(long, value_parser, value_name = "DIR")]
+    pub members_dir: Option<String>,
+
+    /// Write `members` as a glob pattern like `crates/*` instead of listing every crate,
+    /// if all member crates are in the same directory
+    #[clap(long, action)]
+    pub glob_members: bool,
+
+    /// Put the version and edition into [workspace.package] and let all crates inherit them
+    #[clap(long, action)]
+    pub workspace_package: bool,
+
+    /// Directory containing user templates for new crates, one subdirectory per template
+    #[clap(long, value_parser, value_name = "DIR")]
+    pub template_dir: Option<String>,
+
+    /// Edition of the new crates
+    #[clap(long, value_parser = ["2015", "2018", "2021"])]
+    pub edition: Option<String>,
+
+    /// License of the new crates, as SPDX expression like `MIT OR Apache-2.0`
+    #[clap(long, value_parser)]
+    pub license: Option<String>,
+
+    /// Author of the new crates (can be used multiple times)
+    #[clap(long = "author", value_parser, value_name = "AUTHOR")]
+    pub authors: Vec<String>,
+
+    /// Do not ask any questions and use the defaults for everything not specified by other flags
+    #[clap(short, long, action)]
+    pub yes: bool,
+}
+
+impl New {
+    /// Whether some information about the workspace has to be asked from the user.
+    pub fn needs_user_input(&self) -> bool {
+        !self.yes && self.from.is_none() && (self.root.is_none() || self.members.is_empty())
+    }
+
+    /// Returns the defaults set by flags, which take precedence over the user configuration file.
+    /// The template directory is not included, because it is relative to the current directory.
+    pub fn defaults(&self) -> Defaults {
+        Defaults {
+            edition: self.edition.clone(),
+            license: self.license.clone(),
+            authors: (!self.authors.is_empty()).then(|| self.authors.clone()),
+            members_dir: self.members_dir.clone(),
+            glob_members: self.glob_members.then_some(true),
+            ..Defaults::default()
+        }
+    }
+}
+
+/// Possible kinds of the root crate.
+#[derive(Clone, ValueEnum)]
+pub enum RootKind {
+    Bin,
+    Lib,
+    None,
+}
+
+/// Describes a member crate given on the command line.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct MemberSpec {
+    pub name: String,
+    pub directory: Option<String>,
+
+    /// Kind of the crate, if `lib`, `bin` or `proc-macro` is given.
+    pub kind: Option<CrateKind>,
+    pub as_dependency: bool,
+
+    /// Whether a derive crate is created for it.
+    pub derive: bool,
+
+    /// Names of the other member crates this crate depends on.
+    pub dependencies: Vec<String>,
+
+    /// Name of the template the files of the crate are created from.
+    pub template: Option<String>,
+
+    /// Targets in addition to the ones created by the template.
+    pub targets: Targets,
+}
+
+impl FromStr for MemberSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+
+        let name = match parts.next() {
+            Some(n) if !n.is_empty() => n.to_owned(),
+            _ => return Err(String::from("the name of the crate is missing")),
+        };
+
+        let mut spec = MemberSpec {
+            name,
+            directory: None,
+            kind: None,
+            as_dependency: false,
+            derive: false,
+            dependencies: Vec::new(),
+            template: None,
+            targets: Targets::default(),
+        };
+
+        for (i, part) in parts.enumerate() {
+            match part {
+                // `lib:bin` is a binary crate that has a library as well.
+                "lib" if spec.kind == Some(CrateKind::Bin) => spec.targets.lib = true,
+                "bin" if spec.kind == Some(CrateKind::Lib) => {
+                    spec.kind = Some(CrateKind::Bin);
+                    spec.targets.lib = true;
+                }
+                "lib" => spec.kind = Some(CrateKind::Lib),
+                "bin" => spec.kind = Some(CrateKind::Bin),
+                "proc-macro" => spec.kind = Some(CrateKind::ProcMacro),
+                "dep" => spec.as_dependency = true,
+                "derive" => spec.derive = true,
+                uses if uses.starts_with("uses=") => {
+                    spec.dependencies = uses["uses=".len()..]
+                        .split(',')
+                        .map(|d| d.trim().to_owned())
+                        .collect();
+                    if spec.dependencies.iter().any(String::is_empty) {
+                        return Err(format!("'{}' contains an empty crate name", uses));
+                    }
+                }
+                template if template.starts_with("template=") => {
+                    match &template["template=".len()..] {
+                        "" => return Err(String::from("the name of the template is missing")),
+                        name => spec.template = Some(name.to_owned()),
+                    }
+                }
+                targets
+                    if ["bins=", "examples=", "tests=", "benches="]
+                        .iter()
+                        .any(|p| targets.starts_with(p)) =>
+                {
+                    let (key, list) = targets.split_once('=').unwrap_or_default();
+                    let list = Targets::parse_list(list)?;
+                    match key {
+                        "bins" => spec.targets.bins = list,
+                        "examples" => spec.targets.examples = list,
+                        "tests" => spec.targets.tests = list,
+                        _ => spec.targets.benches = list,
+                    }
+                }
+                dir if i == 0 && !dir.is_empty() => spec.directory = Some(dir.to_owned()),
+                other => return Err(format!("unexpected part '{}'", other)),
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_member_spec() {
+        let spec: MemberSpec = "foo".parse().unwrap();
+        assert_eq!(spec.name, "foo");
+        assert_eq!(spec.directory, None);
+        assert_eq!(spec.kind, None);
+        assert!(!spec.as_dependency);
+
+        let spec: MemberSpec = "foo:crates/foo:bin:dep".parse().unwrap();
+        assert_eq!(spec.directory, Some(String::from("crates/foo")));
+        assert_eq!(spec.kind, Some(CrateKind::Bin));
+        assert!(spec.as_dependency);
+
+        let spec: MemberSpec = "foo:dep".parse().unwrap();
+        assert_eq!(spec.directory, None);
+        assert!(spec.as_dependency);
+
+        let spec: MemberSpec = "cli:bin:uses=core,model".parse().unwrap();
+        assert_eq!(spec.directory, None);
+        assert_eq!(spec.kind, Some(CrateKind::Bin));
+        assert_eq!(spec.dependencies, vec!["core", "model"]);
+
+        let spec: MemberSpec = "ffi:crates/ffi:template=cdylib".parse().unwrap();
+        assert_eq!(spec.directory, Some(String::from("crates/ffi")));
+        assert_eq!(spec.template.as_deref(), Some("cdylib"));
+
+        let spec: MemberSpec = "macros:proc-macro".parse().unwrap();
+        assert_eq!(spec.kind, Some(CrateKind::ProcMacro));
+        assert!(!spec.derive);
+
+        let spec: MemberSpec = "model:lib:derive".parse().unwrap();
+        assert_eq!(spec.kind, Some(CrateKind::Lib));
+        assert!(spec.derive);
+
+        let spec: MemberSpec = "app:lib:bin:bins=admin,import=tools/import.rs:examples=demo"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.kind, Some(CrateKind::Bin));
+        assert!(spec.targets.lib);
+        assert_eq!(spec.targets.bins.len(), 2);
+        assert_eq!(
+            spec.targets.bins[1].path.as_deref(),
+            Some("tools/import.rs")
+        );
+        assert_eq!(spec.targets.examples[0].name, "demo");
+        assert!("app:bins=".parse::<MemberSpec>().is_err());
+
+        assert!("cli:uses=".parse::<MemberSpec>().is_err());
+        assert!("cli:template=".parse::<MemberSpec>().is_err());
+        assert!("cli:uses=core,,model".parse::<MemberSpec>().is_err());
+
+        assert!("".parse::<MemberSpec>().is_err());
+        assert!("foo:lib:dir".parse::<MemberSpec>().is_err());
+    }
 }
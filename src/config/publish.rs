@@ -0,0 +1,25 @@
+//! Subcommand 'publish'
+//!
+
+use clap::Args;
+
+/// Stores futher arguments for the sub-command 'publish'
+#[derive(Args)]
+pub struct Publish {
+    /// Only print which crates would be published and which Cargo.toml files would change
+    #[clap(long, action)]
+    pub dry_run: bool,
+
+    /// Registry to publish to, passed on to `cargo publish`
+    #[clap(long, value_parser)]
+    pub registry: Option<String>,
+
+    /// Allow publishing with uncommitted changes, passed on to `cargo publish`. It is
+    /// always passed if version requirements had to be added to Cargo.toml files
+    #[clap(long, action)]
+    pub allow_dirty: bool,
+
+    /// Don't build the crates before publishing, passed on to `cargo publish`
+    #[clap(long, action)]
+    pub no_verify: bool,
+}
@@ -0,0 +1,20 @@
+//! Subcommand 'remove'
+//!
+
+use clap::Args;
+
+/// Stores futher arguments for the sub-command 'remove'
+#[derive(Args)]
+pub struct Remove {
+    /// Name of the crate that should be removed
+    #[clap(value_parser)]
+    pub crate_name: String,
+
+    /// Remove the crate even if other members still use it in their source code
+    #[clap(short, long, action)]
+    pub force: bool,
+
+    /// Delete the directory of the crate without asking
+    #[clap(short, long, action)]
+    pub delete: bool,
+}
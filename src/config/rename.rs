@@ -0,0 +1,28 @@
+//! Subcommand 'rename'
+//!
+
+use clap::Args;
+
+/// Stores futher arguments for the sub-command 'rename'
+#[derive(Args)]
+pub struct Rename {
+    /// Current name of the crate
+    #[clap(value_parser)]
+    pub old_name: String,
+
+    /// New name of the crate
+    #[clap(value_parser)]
+    pub new_name: String,
+
+    /// Also rename the directory of the crate to the new name
+    #[clap(long, action)]
+    pub move_dir: bool,
+
+    /// Only print the changes without applying them
+    #[clap(long, action)]
+    pub dry_run: bool,
+
+    /// Apply the changes without asking
+    #[clap(short, long, action)]
+    pub yes: bool,
+}
@@ -0,0 +1,116 @@
+//! Targets of new crates
+//!
+//! Besides the targets created by its template, a new crate can have a library target,
+//! binaries, examples, tests and benchmarks. Each of them is given as `name[=path]`.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Targets of a crate in addition to the ones created by its template.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Targets {
+    /// Whether a binary crate also has a library target in `src/lib.rs`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lib: bool,
+
+    /// Binaries, which are put into `src/bin` unless they are named like the crate.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bins: Vec<TargetSpec>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<TargetSpec>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<TargetSpec>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub benches: Vec<TargetSpec>,
+}
+
+/// A target given as `name[=path]`. Without a path, its file is put
+/// where cargo finds it by itself, like `examples/<name>.rs`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct TargetSpec {
+    pub name: String,
+
+    /// Path of the root source file, relative to the directory of the crate.
+    pub path: Option<String>,
+}
+
+impl Targets {
+    /// Parses the value of a part like `bins=server,admin=tools/admin.rs` of a member crate.
+    pub fn parse_list(list: &str) -> Result<Vec<TargetSpec>, String> {
+        list.split(',').map(|t| t.trim().parse()).collect()
+    }
+}
+
+impl FromStr for TargetSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, path) = match s.split_once('=') {
+            Some((name, path)) => (name, Some(path)),
+            None => (s, None),
+        };
+
+        if name.is_empty() {
+            return Err(String::from("the name of the target is missing"));
+        }
+        if name.contains(['/', '\\']) || name.contains(char::is_whitespace) {
+            return Err(format!("'{}' is not a valid target name", name));
+        }
+        if path.is_some_and(|p| !p.ends_with(".rs")) {
+            return Err(format!("the path of the target {} is not a .rs file", name));
+        }
+
+        Ok(TargetSpec {
+            name: name.to_owned(),
+            path: path.map(str::to_owned),
+        })
+    }
+}
+
+impl TryFrom<String> for TargetSpec {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TargetSpec> for String {
+    fn from(target: TargetSpec) -> String {
+        match target.path {
+            Some(path) => format!("{}={}", target.name, path),
+            None => target.name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target_spec() {
+        let target: TargetSpec = "server".parse().unwrap();
+        assert_eq!(target.name, "server");
+        assert_eq!(target.path, None);
+
+        let target: TargetSpec = "admin=tools/admin.rs".parse().unwrap();
+        assert_eq!(target.path.as_deref(), Some("tools/admin.rs"));
+        assert_eq!(String::from(target), "admin=tools/admin.rs");
+
+        let targets = Targets::parse_list("a, b=src/b.rs").unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].name, "b");
+
+        for invalid in ["", "=src/a.rs", "a b", "bin/a", "a=src/a", "a,,b"] {
+            assert!(Targets::parse_list(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Subcommand 'version'
+//!
+
+use std::str::FromStr;
+
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+
+/// Stores futher arguments for the sub-command 'version'
+#[derive(Args)]
+pub struct Version {
+    /// How to change the version: patch, minor, major or an exact version like 1.2.0
+    #[clap(value_parser)]
+    pub bump: VersionBump,
+
+    /// Crates whose versions should be changed. Defaults to all crates
+    #[clap(value_parser)]
+    pub crates: Vec<String>,
+
+    /// Whether all crates share one version. Defaults to the `version-mode`
+    /// in `[workspace.metadata.ws-manage]` or independent
+    #[clap(long, value_enum)]
+    pub mode: Option<VersionMode>,
+
+    /// Only print the new versions without changing anything
+    #[clap(long, action)]
+    pub dry_run: bool,
+}
+
+/// A change of a version.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum VersionBump {
+    Level(BumpLevel),
+    Exact(semver::Version),
+}
+
+impl FromStr for VersionBump {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "major" => Ok(VersionBump::Level(BumpLevel::Major)),
+            "minor" => Ok(VersionBump::Level(BumpLevel::Minor)),
+            "patch" => Ok(VersionBump::Level(BumpLevel::Patch)),
+            _ => match s.parse() {
+                Ok(v) => Ok(VersionBump::Exact(v)),
+                Err(_) => Err(String::from(
+                    "expected patch, minor, major or a version like 1.2.3",
+                )),
+            },
+        }
+    }
+}
+
+/// Part of a version that is increased.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum BumpLevel {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// How the versions of the crates of a workspace relate to each other.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum VersionMode {
+    /// All crates always have the same version
+    Lockstep,
+
+    /// Every crate has its own version
+    Independent,
+}
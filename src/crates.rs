@@ -1,22 +1,162 @@
-use crate::fs::{self, CargoToml, PackageSection};
+use crate::config::{CrateKind, Defaults, Targets};
+use crate::fs::{self, CargoToml, Dependency, TargetSection};
 use crate::input;
+use crate::package::{DependencyKind, Package, PackageDependency, TargetKind};
+use crate::template::{self, Placeholders, Template};
+use crate::workspace::Metadata;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::path::Path;
+use toml_edit::Document;
 
 /// This struct makes a directory for a crate and a Cargo.toml,
 /// based on user input.
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct Crate {
+    #[serde(skip)]
     pub is_root: bool,
+
+    #[serde(rename = "name", default, skip_serializing_if = "String::is_empty")]
     pub crate_name: String,
+
+    #[serde(rename = "directory", default, skip_serializing_if = "is_current_dir")]
     pub directory_name: String,
+
+    #[serde(default, skip_serializing_if = "is_false")]
     pub as_dependency: bool,
-    pub binary: bool,
+
+    #[serde(default)]
+    pub kind: CrateKind,
+
+    /// Whether the proc-macro crate returned by [Crate::derive_crate] is created
+    /// along with this library crate, which depends on it and re-exports its macros.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub derive: bool,
+
+    /// Names of the other member crates this crate depends on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+
+    /// Name of the template the files of the crate are created from.
+    /// Without one, the built-in template `bin` or `lib` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// Targets in addition to the ones created by the template, like more binaries.
+    #[serde(flatten)]
+    pub targets: Targets,
+
+    /// The template, once it has been loaded by [Crate::load_template].
+    #[serde(skip)]
+    loaded_template: Option<Template>,
+}
+
+/// Returns the directory of a new member crate called `name`,
+/// which is inside `members_dir` if it is given.
+pub fn default_directory(name: &str, members_dir: Option<&str>) -> String {
+    match members_dir {
+        Some(dir) => format!("{}/{}", dir.trim_end_matches('/'), name),
+        None => name.to_owned(),
+    }
+}
+
+fn is_current_dir(dir: &String) -> bool {
+    dir.is_empty() || dir == "."
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+/// Returns the path cargo finds a target called `name` at by itself,
+/// like `src/main.rs` for the binary named like the crate.
+pub fn default_target_path(kind: TargetKind, name: &str, crate_name: &str) -> String {
+    match kind {
+        TargetKind::Lib | TargetKind::ProcMacro => String::from("src/lib.rs"),
+        TargetKind::Bin if name == crate_name => String::from("src/main.rs"),
+        TargetKind::Bin => format!("src/bin/{}.rs", name),
+        TargetKind::Example => format!("examples/{}.rs", name),
+        TargetKind::Test => format!("tests/{}.rs", name),
+        TargetKind::Bench => format!("benches/{}.rs", name),
+    }
 }
 
 impl Crate {
-    pub fn new_from_user_input(is_root: bool, root_exists: bool, indent: bool) -> Crate {
-        let mut crate_name = String::new();
-        let mut directory_name = String::from(".");
+    /// Creates the root crate of a workspace.
+    pub fn new_root(kind: CrateKind) -> Crate {
+        Crate {
+            is_root: true,
+            crate_name: String::new(),
+            directory_name: String::from("."),
+            as_dependency: false,
+            kind,
+            derive: false,
+            dependencies: Vec::new(),
+            template: None,
+            targets: Targets::default(),
+            loaded_template: None,
+        }
+    }
+
+    /// Creates a member crate of a workspace.
+    pub fn new_member(
+        crate_name: &str,
+        directory_name: &str,
+        kind: CrateKind,
+        as_dependency: bool,
+    ) -> Crate {
+        Crate {
+            is_root: false,
+            crate_name: crate_name.to_owned(),
+            directory_name: directory_name.to_owned(),
+            as_dependency,
+            kind,
+            derive: false,
+            dependencies: Vec::new(),
+            template: None,
+            targets: Targets::default(),
+            loaded_template: None,
+        }
+    }
+
+    /// Describes the crate and its dependencies on other member crates as a [Package],
+    /// like it would be loaded once the crate has been written.
+    pub fn as_package(&self) -> Package {
+        Package {
+            name: self.crate_name.clone(),
+            version: None,
+            directory: Path::new(&self.directory_name).to_path_buf(),
+            publish: true,
+            targets: Vec::new(),
+            dependencies: self
+                .dependencies
+                .iter()
+                .map(|d| PackageDependency {
+                    key: d.clone(),
+                    package: d.clone(),
+                    kind: DependencyKind::Normal,
+                    platform: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Asks the user for all information about the crate. If `crate_name`,
+    /// `directory_name` or `kind` are given, the corresponding prompts are skipped.
+    /// The suggested directory and the default answer of the library prompt are taken from `defaults`.
+    pub fn new_from_user_input(
+        is_root: bool,
+        root_exists: bool,
+        indent: bool,
+        crate_name: Option<&str>,
+        directory_name: Option<&str>,
+        kind: Option<CrateKind>,
+        defaults: &Defaults,
+    ) -> Crate {
+        let mut name = String::new();
+        let mut directory = String::from(".");
         let mut as_dependency = false;
 
         let indent_str = "    ";
@@ -37,17 +177,26 @@ impl Crate {
             )
         };
 
-        let library_prompt = {
+        let kind_prompt = {
             &format!(
-                "{}Is this crate a library crate?",
+                "{}Kind of the crate (lib, bin or proc-macro)?",
                 if indent { indent_str } else { "" }
             )
         };
 
         if !is_root {
-            crate_name = input::get_string(crate_name_prompt, None, Some(false));
-            directory_name =
-                input::get_string(directory_name_prompt, Some(crate_name.clone()), None);
+            name = match crate_name {
+                Some(n) => n.to_owned(),
+                None => input::get_string(crate_name_prompt, None, Some(false)),
+            };
+            directory = match directory_name {
+                Some(d) => d.to_owned(),
+                None => input::get_string(
+                    directory_name_prompt,
+                    Some(default_directory(&name, defaults.members_dir.as_deref())),
+                    None,
+                ),
+            };
 
             if root_exists {
                 as_dependency =
@@ -55,98 +204,326 @@ impl Crate {
             }
         }
 
-        let binary = !input::prompt_yes_no(
-            library_prompt,
-            if is_root {
-                input::DefaultBool::No
-            } else {
-                input::DefaultBool::Yes
-            },
-        )
-        .unwrap();
+        let kind = kind.unwrap_or_else(|| {
+            let default = match is_root {
+                true => CrateKind::Bin,
+                false => defaults.crate_kind.unwrap_or_default(),
+            };
+            loop {
+                let answer = input::get_string(kind_prompt, Some(default.name().to_owned()), None);
+                match CrateKind::from_str(&answer, true) {
+                    Ok(kind) => break kind,
+                    Err(_) => println!(
+                        "{}{} is not one of lib, bin or proc-macro",
+                        if indent { indent_str } else { "" },
+                        answer
+                    ),
+                }
+            }
+        });
 
         Crate {
             is_root,
-            crate_name,
-            directory_name,
+            crate_name: name,
+            directory_name: directory,
             as_dependency,
-            binary,
+            kind,
+            derive: false,
+            dependencies: Vec::new(),
+            template: None,
+            targets: Targets::default(),
+            loaded_template: None,
         }
     }
 
-    pub fn write_to_disk(&self, root_dir: &str) {
+    /// Asks the user whether a derive crate is created along with this crate,
+    /// if it is a library member crate.
+    pub fn derive_from_user_input(&mut self, indent: bool) {
+        if self.is_root || !self.can_have_derive_crate() {
+            return;
+        }
+
+        let prompt = format!(
+            "{}Create the proc-macro crate {} for derive macros of this crate?",
+            if indent { "    " } else { "" },
+            self.derive_crate_name()
+        );
+        self.derive = input::prompt_yes_no(&prompt, input::DefaultBool::No).unwrap();
+    }
+
+    /// Whether other crates can depend on the crate, because it has a library target.
+    pub fn has_lib(&self) -> bool {
+        self.kind.is_lib() || self.targets.lib
+    }
+
+    /// Whether the crate has a library that can re-export the macros of a derive crate.
+    pub fn can_have_derive_crate(&self) -> bool {
+        self.has_lib() && self.kind != CrateKind::ProcMacro
+    }
+
+    /// Name of the derive crate of this crate, like `foo-derive` for `foo`.
+    pub fn derive_crate_name(&self) -> String {
+        format!("{}-derive", self.crate_name)
+    }
+
+    /// Creates the proc-macro crate for the derive macros of this crate,
+    /// which is put into a directory next to the one of this crate.
+    pub fn derive_crate(&self) -> Crate {
+        let name = self.derive_crate_name();
+        let directory = match Path::new(&self.directory_name).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                parent.join(&name).to_string_lossy().into_owned()
+            }
+            _ => name.clone(),
+        };
+
+        Crate::new_member(&name, &directory, CrateKind::ProcMacro, false)
+    }
+
+    /// Loads the template of the crate, preferring the user templates in `template_dir`.
+    /// An explicitly chosen template decides the kind of the crate.
+    pub fn load_template(&mut self, template_dir: Option<&Path>) -> Result<(), String> {
+        let template = Template::load(self.template_name(), template_dir)?;
+        if self.template.is_some() {
+            self.kind = template.crate_kind();
+        }
+        self.loaded_template = Some(template);
+
+        Ok(())
+    }
+
+    fn template_name(&self) -> &str {
+        match &self.template {
+            Some(t) => t,
+            None => self.kind.name(),
+        }
+    }
+
+    /// Returns the loaded template or loads a built-in one. Errors will be
+    /// handled by displaying an error message and exiting with code 1.
+    fn template_or_handle_error(&self) -> Cow<'_, Template> {
+        if let Some(t) = &self.loaded_template {
+            return Cow::Borrowed(t);
+        }
+
+        match Template::load(self.template_name(), None) {
+            Ok(t) => Cow::Owned(t),
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Returns the sections the template adds to the Cargo.toml of the crate,
+    /// which is called `project_name` if it is the root crate.
+    pub fn template_manifest(&self, project_name: &str, metadata: &Metadata) -> Option<Document> {
+        let template = self.template_or_handle_error();
+        match template.manifest(&self.placeholders(project_name, metadata)) {
+            Ok(m) => m,
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Returns the name of the crate, which is `project_name` for the root crate.
+    fn name<'a>(&'a self, project_name: &'a str) -> &'a str {
+        match self.is_root {
+            true => project_name,
+            false => &self.crate_name,
+        }
+    }
+
+    fn placeholders<'a>(
+        &'a self,
+        project_name: &'a str,
+        metadata: &'a Metadata,
+    ) -> Placeholders<'a> {
+        Placeholders {
+            crate_name: self.name(project_name),
+            project_name,
+            authors: &metadata.authors,
+        }
+    }
+
+    /// Returns the targets declared in addition to the ones of the template, together with
+    /// the paths of their root source files. The crate is called `project_name` if it is the root.
+    fn declared_targets<'a>(&'a self, project_name: &'a str) -> Vec<(TargetKind, &'a str, String)> {
+        let crate_name = self.name(project_name);
+
+        let mut targets = Vec::new();
+        if self.targets.lib {
+            targets.push((TargetKind::Lib, crate_name, String::from("src/lib.rs")));
+        }
+        let lists = [
+            (TargetKind::Bin, &self.targets.bins),
+            (TargetKind::Example, &self.targets.examples),
+            (TargetKind::Test, &self.targets.tests),
+            (TargetKind::Bench, &self.targets.benches),
+        ];
+        for (kind, list) in lists {
+            for t in list {
+                let path = match &t.path {
+                    Some(p) => p.clone(),
+                    None => default_target_path(kind, &t.name, crate_name),
+                };
+                targets.push((kind, t.name.as_str(), path));
+            }
+        }
+
+        targets
+    }
+
+    /// Checks that no two declared targets of the same kind have the same name
+    /// and that no two of them share a root source file.
+    pub fn validate_targets(&self, project_name: &str) -> Result<(), String> {
+        let targets = self.declared_targets(project_name);
+        for (i, (kind, name, path)) in targets.iter().enumerate() {
+            for (other_kind, other_name, other_path) in &targets[..i] {
+                if kind == other_kind && name == other_name {
+                    return Err(format!(
+                        "{} has multiple targets named {}",
+                        self.name(project_name),
+                        name
+                    ));
+                }
+                if path == other_path {
+                    return Err(format!(
+                        "{} has multiple targets in {}",
+                        self.name(project_name),
+                        path
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the sections of the declared targets that cargo does not find by their path,
+    /// and of all benchmarks, which have their own `main` because the harness needs nightly.
+    pub fn target_sections<'a>(&'a self, project_name: &'a str) -> Vec<TargetSection<'a>> {
+        let crate_name = self.name(project_name);
+
+        let mut sections = Vec::new();
+        let lists = [
+            ("bin", TargetKind::Bin, &self.targets.bins),
+            ("example", TargetKind::Example, &self.targets.examples),
+            ("test", TargetKind::Test, &self.targets.tests),
+            ("bench", TargetKind::Bench, &self.targets.benches),
+        ];
+        for (key, kind, list) in lists {
+            for t in list {
+                let path = t
+                    .path
+                    .as_deref()
+                    .filter(|p| *p != default_target_path(kind, &t.name, crate_name));
+                let harness = (kind == TargetKind::Bench).then_some(false);
+                if path.is_some() || harness.is_some() {
+                    sections.push(TargetSection {
+                        kind: key,
+                        name: Some(&t.name),
+                        path,
+                        harness,
+                    });
+                }
+            }
+        }
+
+        sections
+    }
+
+    /// Writes the crate into the workspace `project_name` at `root_dir`. For member
+    /// crates, a Cargo.toml with the given dependencies and metadata is written as well.
+    pub fn write_to_disk(
+        &self,
+        root_dir: &str,
+        project_name: &str,
+        deps: &Vec<Dependency>,
+        metadata: &Metadata,
+    ) {
         let dirname = format!("{}/{}", root_dir, self.directory_name);
 
         if !self.is_root {
+            // The directory can be nested like `crates/foo`, so its parents are created first.
+            if let Some(parent) = Path::new(&dirname).parent() {
+                if let Err(e) = fs::mkdir(parent, true) {
+                    println!(
+                        "Error occurred when creating directory {}: {}",
+                        parent.display(),
+                        e
+                    );
+                    std::process::exit(1);
+                }
+            }
             fs::create_dir_or_handle_error(&dirname);
         }
 
-        self.write_src(&dirname);
+        self.write_src(&dirname, project_name, metadata);
 
         if self.is_root {
             return;
         }
 
+        let template = self.template_manifest(project_name, metadata);
         fs::write_cargo_toml_or_handle_error(
             &dirname,
             &CargoToml {
-                package: Some(PackageSection {
-                    pkg_name: &self.crate_name,
-                    pkg_version: "0.1.0",
-                    pkg_edition: "2021",
-                }),
-                dependencies: None,
+                package: Some(metadata.package_section(&self.crate_name)),
+                dependencies: if deps.is_empty() { None } else { Some(deps) },
+                targets: self.target_sections(project_name),
                 workspace: None,
+                template: template.as_ref(),
             },
         );
     }
 
-    fn write_src(&self, crate_dirname: &String) {
-        let src_dir = format!("{}/src", crate_dirname);
-        fs::create_dir_or_handle_error(&src_dir);
+    /// Writes the files of the template of the crate into its directory, and
+    /// a root source file for every declared target the template has none for.
+    fn write_src(&self, crate_dirname: &str, project_name: &str, metadata: &Metadata) {
+        let template = self.template_or_handle_error();
 
-        let filename = if self.binary {
-            format!("{}/src/main.rs", crate_dirname)
-        } else {
-            format!("{}/src/lib.rs", crate_dirname)
-        };
+        let mut files = template.files(&self.placeholders(project_name, metadata));
+        for (kind, _, path) in self.declared_targets(project_name) {
+            if files.iter().all(|(p, _)| *p != path) {
+                files.push((path, template::target_source(kind).to_owned()));
+            }
+        }
 
-        let result = if self.binary {
-            fs::write_file(
-                Path::new(&filename),
-                &String::from(
-                    "\
-fn main() {
-    println!(\"Hello, world!\");
-}
-",
-                ),
-                false,
-            )
-        } else {
-            fs::write_file(
-                Path::new(&filename),
-                &String::from(
-                    "\
-pub fn run() {
-    println!(\"Hello, world!\");
-}
-",
-                ),
-                false,
-            )
-        };
+        for (path, mut content) in files {
+            if self.derive && path == "src/lib.rs" {
+                content = format!(
+                    "pub use {}::*;\n\n{}",
+                    self.derive_crate_name().replace('-', "_"),
+                    content
+                );
+            }
 
-        match result {
-            Ok(_) => {}
-            Err(e) => match e {
-                fs::IOError::FsError(e) => {
-                    println!("Error writing file {}: {}", filename, e);
-                }
-                _ => {
-                    panic!("Unexpected error while writing file {}", filename);
+            let filename = Path::new(crate_dirname).join(&path);
+            if let Some(parent) = filename.parent() {
+                if let Err(e) = fs::mkdir(parent, true) {
+                    println!(
+                        "Error occurred when creating directory {}: {}",
+                        parent.display(),
+                        e
+                    );
+                    std::process::exit(1);
                 }
-            },
-        };
+            }
+
+            match fs::write_file(&filename, &content, false) {
+                Ok(_) => {}
+                Err(e) => match e {
+                    fs::IOError::FsError(e) => {
+                        println!("Error writing file {}: {}", filename.display(), e);
+                    }
+                    _ => {
+                        panic!("Unexpected error while writing file {}", filename.display());
+                    }
+                },
+            };
+        }
     }
 }
@@ -0,0 +1,148 @@
+//! This module invokes the execution of the 'DEP' command and its sub-commands,
+//! which manage the dependencies of a single crate of a workspace.
+
+use std::path::Path;
+
+use crate::config::{Dep, DepAdd, DepCommands};
+use crate::fs::{self, Dependency, DependencySource, GitReference};
+use crate::package;
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &Dep) {
+    match &cfg.command {
+        DepCommands::Add(add_cfg) => add(add_cfg),
+    }
+}
+
+/// Adds a dependency to one crate of the workspace.
+fn add(cfg: &DepAdd) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let package = match ws.package(&cfg.crate_name) {
+        Some(p) => p,
+        None => {
+            println!("{} is not a member of the workspace", cfg.crate_name);
+            std::process::exit(1);
+        }
+    };
+    let crate_dir = ws.root_path.join(&package.directory);
+    let mut manifest = fs::load_manifest_or_handle_error(&crate_dir.to_string_lossy());
+
+    let dependency = match dependency(cfg, &crate_dir) {
+        Ok(d) => d,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let root = fs::load_manifest_or_handle_error(&ws.root_path.to_string_lossy());
+    if dependency.source == DependencySource::Workspace
+        && root.workspace_dependency(&dependency.name).is_none()
+    {
+        println!(
+            "{} is not in [workspace.dependencies], so a version requirement, --path or --git is needed",
+            dependency.name
+        );
+        std::process::exit(1);
+    }
+
+    let table = cfg.table.name();
+    let target = cfg.target.as_deref();
+    let table_name = match target {
+        Some(t) => format!("target.\"{}\".{}", t, table),
+        None => table.to_owned(),
+    };
+    if manifest
+        .dependency(table, target, &dependency.name)
+        .is_some()
+    {
+        println!(
+            "{} already has {} in [{}]",
+            cfg.crate_name, dependency.name, table_name
+        );
+        std::process::exit(1);
+    }
+
+    manifest.set_dependency(
+        table,
+        target,
+        &dependency.name,
+        toml_edit::value(dependency.to_value()),
+    );
+    fs::save_manifest_or_handle_error(&manifest);
+    println!(
+        "Added {} to [{}] of {}",
+        dependency.name, table_name, cfg.crate_name
+    );
+}
+
+/// Builds the dependency from the arguments and checks them. Paths are
+/// given relative to the current directory and written relative to `crate_dir`.
+fn dependency(cfg: &DepAdd, crate_dir: &Path) -> Result<Dependency, String> {
+    let (name, version) = package::parse_dependency_spec(&cfg.dependency)?;
+    let version = version.map(str::to_owned);
+    if let Some(rename) = cfg.rename.as_deref().filter(|r| !package::is_valid_name(r)) {
+        return Err(format!("{} is not a valid crate name", rename));
+    }
+
+    let source = match (&cfg.path, &cfg.git) {
+        (Some(path), _) => {
+            if !Path::new(path).join("Cargo.toml").is_file() {
+                return Err(format!("There is no Cargo.toml in {}", path));
+            }
+            // The root of the workspace can be absolute, so both paths are made absolute.
+            let absolute = |p: &Path| {
+                std::fs::canonicalize(p)
+                    .map_err(|e| format!("Resolving {} failed with error: {}", p.display(), e))
+            };
+            DependencySource::Path {
+                path: fs::relative_path(&absolute(crate_dir)?, &absolute(Path::new(path))?)
+                    .to_string_lossy()
+                    .into_owned(),
+                version,
+            }
+        }
+        (None, Some(url)) => DependencySource::Git {
+            url: url.clone(),
+            reference: match (&cfg.branch, &cfg.tag, &cfg.rev) {
+                (Some(b), _, _) => Some(GitReference::Branch(b.clone())),
+                (_, Some(t), _) => Some(GitReference::Tag(t.clone())),
+                (_, _, Some(r)) => Some(GitReference::Rev(r.clone())),
+                _ => None,
+            },
+            version,
+        },
+        (None, None) => match version {
+            Some(version) => DependencySource::Registry {
+                version,
+                registry: cfg.registry.clone(),
+            },
+            None if cfg.registry.is_some() => {
+                return Err(format!(
+                    "A dependency from another registry needs a version requirement, like {}@1.0",
+                    name
+                ))
+            }
+            None => DependencySource::Workspace,
+        },
+    };
+
+    // Inherited dependencies can only add features and be optional,
+    // everything else is taken from [workspace.dependencies].
+    if source == DependencySource::Workspace && (cfg.rename.is_some() || cfg.no_default_features) {
+        return Err(format!(
+            "{} is inherited from [workspace.dependencies], so it cannot be renamed or change its default features",
+            name
+        ));
+    }
+
+    let mut dependency = Dependency::new(cfg.rename.as_deref().unwrap_or(name), source);
+    if cfg.rename.is_some() {
+        dependency.package = Some(name.to_owned());
+    }
+    dependency.features = cfg.features.clone();
+    dependency.optional = cfg.optional;
+    dependency.default_features = !cfg.no_default_features;
+
+    Ok(dependency)
+}
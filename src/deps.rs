@@ -0,0 +1,438 @@
+//! This module invokes the execution of the 'DEPS' command and its sub-commands,
+//! which manage the external dependencies shared by the crates of a workspace.
+
+use std::path::PathBuf;
+
+use semver::{Comparator, Op, Version, VersionReq};
+use toml_edit::Item;
+
+use crate::config::{Deps, DepsCommands};
+use crate::fs::{self, cargo_toml, Dependency, DependencySource, ManifestDocument};
+use crate::workspace::Workspace;
+
+mod add;
+mod check;
+mod hoist;
+
+pub fn run(cfg: &Deps) {
+    match &cfg.command {
+        DepsCommands::Hoist(hoist_cfg) => hoist::run(hoist_cfg),
+        DepsCommands::Add(add_cfg) => add::run(add_cfg),
+        DepsCommands::Check(check_cfg) => check::run(check_cfg),
+    }
+}
+
+/// A Cargo.toml of the workspace that might get changed, together with its original content.
+struct Manifest {
+    /// Name of the package, if the Cargo.toml has a \[package\] section.
+    name: Option<String>,
+
+    /// Directory of the Cargo.toml, relative to the root of the workspace.
+    directory: PathBuf,
+
+    content: ManifestDocument,
+    original: String,
+}
+
+/// Loads the Cargo.toml files of all packages. The root Cargo.toml is always the first one,
+/// even if there is no root package.
+fn load_manifests(ws: &Workspace) -> Vec<Manifest> {
+    let mut packages = vec![(
+        ws.root_package().map(|p| p.name.clone()),
+        PathBuf::from("."),
+    )];
+    packages.extend(
+        ws.members()
+            .map(|p| (Some(p.name.clone()), p.directory.clone())),
+    );
+
+    packages
+        .into_iter()
+        .map(|(name, directory)| {
+            let content =
+                fs::load_manifest_or_handle_error(&ws.root_path.join(&directory).to_string_lossy());
+            Manifest {
+                name,
+                directory,
+                original: content.to_string(),
+                content,
+            }
+        })
+        .collect()
+}
+
+/// Writes all manifests that have been changed.
+fn save_manifests(manifests: &[Manifest]) {
+    for manifest in manifests {
+        if manifest.content.to_string() != manifest.original {
+            fs::save_manifest_or_handle_error(&manifest.content);
+            println!(
+                "Updated {}",
+                manifest.directory.join("Cargo.toml").display()
+            );
+        }
+    }
+}
+
+/// A dependency from crates.io that is specified by nothing but a version requirement,
+/// optionally with features, so it can be moved into `[workspace.dependencies]`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct RegistryDependency {
+    requirement: String,
+
+    /// Name of the package, if the dependency is renamed.
+    package: Option<String>,
+
+    default_features: bool,
+}
+
+impl RegistryDependency {
+    /// Reads a dependency like `"1.0"` or `{ version = "1.0", features = ["derive"] }`.
+    /// Returns [None] for all other dependencies, like path, git or inherited ones.
+    fn from_item(item: &Item) -> Option<RegistryDependency> {
+        if let Some(requirement) = item.as_str() {
+            return Some(RegistryDependency {
+                requirement: requirement.trim().to_owned(),
+                package: None,
+                default_features: true,
+            });
+        }
+
+        let table = item.as_table_like()?;
+        let allowed = [
+            "version",
+            "package",
+            "features",
+            "optional",
+            "default-features",
+            "default_features",
+        ];
+        if table.iter().any(|(k, _)| !allowed.contains(&k)) {
+            return None;
+        }
+
+        Some(RegistryDependency {
+            requirement: table.get("version")?.as_str()?.trim().to_owned(),
+            package: match table.get("package") {
+                Some(p) => Some(p.as_str()?.to_owned()),
+                None => None,
+            },
+            default_features: table
+                .get("default-features")
+                .or_else(|| table.get("default_features"))
+                .and_then(|d| d.as_bool())
+                .unwrap_or(true),
+        })
+    }
+
+    /// Whether both dependencies refer to the same package with the same default features,
+    /// which is needed to share one entry in `[workspace.dependencies]`.
+    fn is_same_package(&self, other: &RegistryDependency) -> bool {
+        self.package == other.package && self.default_features == other.default_features
+    }
+
+    /// Creates the entry for `[workspace.dependencies]`, which
+    /// is a plain requirement if nothing else has to be set.
+    fn workspace_entry(&self) -> Item {
+        let mut entry = Dependency::new(
+            "",
+            DependencySource::Registry {
+                version: self.requirement.clone(),
+                registry: None,
+            },
+        );
+        entry.package = self.package.clone();
+        entry.default_features = self.default_features;
+        toml_edit::value(entry.to_value())
+    }
+}
+
+/// Collects the dependencies of all crates that are specified by a version requirement,
+/// together with their keys and the names of the crates.
+fn registry_dependencies(manifests: &mut [Manifest]) -> Vec<(String, String, RegistryDependency)> {
+    let mut dependencies = Vec::new();
+    for manifest in manifests.iter_mut() {
+        let name = manifest.name.clone().unwrap_or_default();
+        for (_, deps) in manifest.content.dependency_tables_mut() {
+            for (key, dep) in deps.iter() {
+                if let Some(d) = RegistryDependency::from_item(dep) {
+                    dependencies.push((key.to_owned(), name.clone(), d));
+                }
+            }
+        }
+    }
+
+    dependencies
+}
+
+/// Returns the highest of the requirements of `candidates`, which are used by the crates
+/// (or other places) they are paired with. That is the requirement which only allows
+/// versions that all others allow, too. Fails if the requirements are incompatible,
+/// so that no version satisfies all of them, or if none of them satisfies all others,
+/// like `>=1.2` and `<1.5`.
+fn highest_requirement(candidates: &[(&str, &RegistryDependency)]) -> Result<String, String> {
+    let mut requirements = Vec::new();
+    for (user, d) in candidates {
+        match VersionReq::parse(&d.requirement) {
+            Ok(r) => requirements.push((user, VersionRange::of(&r), &d.requirement)),
+            Err(_) => {
+                return Err(format!(
+                    "the requirement {} of {} is invalid",
+                    d.requirement, user
+                ))
+            }
+        }
+    }
+
+    let (first_user, first_range, first_string) = &requirements[0];
+    let mut common = first_range.clone();
+    for (user, range, string) in &requirements {
+        common = common.intersection(range);
+        if common.is_empty() {
+            return Err(format!(
+                "the requirements {} of {} and {} of {} are incompatible",
+                first_string, first_user, string, user
+            ));
+        }
+    }
+
+    requirements
+        .iter()
+        .filter(|(_, range, _)| range.is_within(&common))
+        .max_by(|(_, a, _), (_, b, _)| a.lower.cmp(&b.lower))
+        .map(|(_, _, s)| s.to_string())
+        .ok_or_else(|| {
+            let strings: Vec<&str> = requirements.iter().map(|(_, _, s)| s.as_str()).collect();
+            format!(
+                "none of the requirements {} allows only versions all others allow",
+                strings.join(", ")
+            )
+        })
+}
+
+/// The versions a requirement allows, from `lower` up to but excluding `upper`.
+/// Pre-releases are treated like any other versions.
+#[derive(Clone)]
+struct VersionRange {
+    lower: Version,
+
+    /// [None] if there is no upper bound, like for `>=1.2`.
+    upper: Option<Version>,
+}
+
+impl VersionRange {
+    /// Returns the range of all comparators of a requirement, like `>=1.2, <1.5`.
+    fn of(requirement: &VersionReq) -> VersionRange {
+        let all = VersionRange {
+            lower: Version::new(0, 0, 0),
+            upper: None,
+        };
+        requirement.comparators.iter().fold(all, |range, c| {
+            range.intersection(&VersionRange::of_comparator(c))
+        })
+    }
+
+    /// Returns the range of a single comparator like `~1.2` or `<=1.4.2`.
+    fn of_comparator(c: &Comparator) -> VersionRange {
+        let lowest = Version {
+            major: c.major,
+            minor: c.minor.unwrap_or(0),
+            patch: c.patch.unwrap_or(0),
+            pre: c.pre.clone(),
+            build: Default::default(),
+        };
+        // The first version after those the comparator names, like `1.3.0` for `1.2`.
+        let after = match (c.minor, c.patch) {
+            (None, _) => Version::new(c.major + 1, 0, 0),
+            (Some(minor), None) => Version::new(c.major, minor + 1, 0),
+            (Some(minor), Some(patch)) => Version::new(c.major, minor, patch + 1),
+        };
+        let range = |lower: Version, upper: Option<Version>| VersionRange { lower, upper };
+
+        match c.op {
+            Op::Exact | Op::Wildcard => range(lowest, Some(after)),
+            Op::Greater => range(after, None),
+            Op::GreaterEq => range(lowest, None),
+            Op::Less => range(Version::new(0, 0, 0), Some(lowest)),
+            Op::LessEq => range(Version::new(0, 0, 0), Some(after)),
+            Op::Tilde => {
+                let upper = match c.minor {
+                    Some(minor) => Version::new(c.major, minor + 1, 0),
+                    None => Version::new(c.major + 1, 0, 0),
+                };
+                range(lowest, Some(upper))
+            }
+            // Zeros on the left make the range smaller, like in `^0.4`, which allows `0.4.x` only.
+            _ => {
+                let upper = match (c.major, c.minor, c.patch) {
+                    (major, _, _) if major > 0 => Version::new(major + 1, 0, 0),
+                    (_, Some(minor), _) if minor > 0 => Version::new(0, minor + 1, 0),
+                    _ => after,
+                };
+                range(lowest, Some(upper))
+            }
+        }
+    }
+
+    fn intersection(&self, other: &VersionRange) -> VersionRange {
+        let upper = match (&self.upper, &other.upper) {
+            (Some(a), Some(b)) => Some(a.min(b).clone()),
+            (a, b) => a.as_ref().or(b.as_ref()).cloned(),
+        };
+        VersionRange {
+            lower: self.lower.clone().max(other.lower.clone()),
+            upper,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.upper
+            .as_ref()
+            .is_some_and(|upper| upper <= &self.lower)
+    }
+
+    /// Whether all versions of this range are in `other`, too.
+    fn is_within(&self, other: &VersionRange) -> bool {
+        self.lower >= other.lower
+            && match (&self.upper, &other.upper) {
+                (_, None) => true,
+                (None, Some(_)) => false,
+                (Some(a), Some(b)) => a <= b,
+            }
+    }
+}
+
+/// Changes the version requirement of a dependency, keeping its formatting.
+/// Returns false if the dependency has no version requirement.
+fn set_requirement(dep: &mut Item, requirement: &str) -> bool {
+    let value = match dep.as_value_mut() {
+        Some(v) if v.is_str() => Some(v),
+        _ => dep
+            .as_table_like_mut()
+            .and_then(|t| t.get_mut("version"))
+            .and_then(|v| v.as_value_mut())
+            .filter(|v| v.is_str()),
+    };
+
+    match value {
+        Some(v) => {
+            cargo_toml::set_string(v, requirement);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Creates the entry `{ workspace = true }` of a crate that uses a workspace dependency.
+fn inherited_entry(features: &[String], optional: bool) -> Item {
+    let mut entry = Dependency::new("", DependencySource::Workspace);
+    entry.features = features.to_vec();
+    entry.optional = optional;
+    toml_edit::value(entry.to_value())
+}
+
+/// Returns the manifest of the package `name`, or exits if it is not part of the workspace.
+fn manifest_index_or_handle_error(ws: &Workspace, manifests: &[Manifest], name: &str) -> usize {
+    let package = match ws.package(name) {
+        Some(p) => p,
+        None => {
+            println!("{} is not a member of the workspace", name);
+            std::process::exit(1);
+        }
+    };
+
+    manifests
+        .iter()
+        .position(|m| fs::normalize_path(&m.directory) == fs::normalize_path(&package.directory))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(toml: &str) -> Option<RegistryDependency> {
+        let manifest = ManifestDocument::parse(PathBuf::from("Cargo.toml"), toml).unwrap();
+        RegistryDependency::from_item(manifest.dependency("dependencies", None, "serde").unwrap())
+    }
+
+    #[test]
+    fn test_registry_dependency() {
+        let d = dependency("[dependencies]\nserde = \"1.0\"").unwrap();
+        assert_eq!(d.requirement, "1.0");
+        assert!(d.default_features);
+
+        let d = dependency(
+            "[dependencies]\nserde = { version = \"1\", package = \"serde1\", default-features = false, features = [\"derive\"] }",
+        )
+        .unwrap();
+        assert_eq!(d.package.as_deref(), Some("serde1"));
+        assert!(!d.default_features);
+
+        let d = dependency("[dependencies.serde]\nversion = \"1.0\"\noptional = true").unwrap();
+        assert_eq!(d.requirement, "1.0");
+
+        assert!(dependency("[dependencies]\nserde = { path = \"../serde\" }").is_none());
+        assert!(dependency("[dependencies]\nserde = { workspace = true }").is_none());
+        assert!(dependency("[dependencies]\nserde = { git = \"https://x\" }").is_none());
+        assert!(dependency("[dependencies]\nserde = { features = [\"derive\"] }").is_none());
+    }
+
+    #[test]
+    fn test_set_requirement() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "[dependencies]\na = \"1.0\" # comment\nb = { version = \"0.3\" }\nc = { path = \"c\" }\n",
+        )
+        .unwrap();
+
+        for (_, deps) in manifest.dependency_tables_mut() {
+            for (key, dep) in deps.iter_mut() {
+                assert_eq!(set_requirement(dep, "2.0"), key.get() != "c");
+            }
+        }
+
+        assert_eq!(
+            manifest.to_string(),
+            "[dependencies]\na = \"2.0\" # comment\nb = { version = \"2.0\" }\nc = { path = \"c\" }\n"
+        );
+    }
+
+    #[test]
+    fn test_highest_requirement() {
+        let highest = |requirements: &[&str]| {
+            let deps: Vec<RegistryDependency> = requirements
+                .iter()
+                .map(|r| RegistryDependency {
+                    requirement: r.to_string(),
+                    package: None,
+                    default_features: true,
+                })
+                .collect();
+            let candidates: Vec<(&str, &RegistryDependency)> =
+                deps.iter().map(|d| ("app", d)).collect();
+            highest_requirement(&candidates)
+        };
+
+        assert_eq!(
+            highest(&["1.0", "1.4.2", "^1.2"]),
+            Ok(String::from("1.4.2"))
+        );
+        assert_eq!(highest(&["1", "^1.9"]), Ok(String::from("^1.9")));
+        assert_eq!(highest(&["0.4", "0.4.17"]), Ok(String::from("0.4.17")));
+        assert!(highest(&["1.0", "2.0"]).is_err());
+        assert!(highest(&["0.4", "0.5"]).is_err());
+        assert_eq!(highest(&["0", "0.1"]), Ok(String::from("0.1")));
+        assert!(highest(&["0.0.3", "0.0.4"]).is_err());
+
+        assert_eq!(highest(&["~1.2", "1.2.5"]).ok(), None);
+        assert_eq!(highest(&["~1.2.5", "1.2"]), Ok(String::from("~1.2.5")));
+        assert_eq!(highest(&["=1.0.3", "1.0"]), Ok(String::from("=1.0.3")));
+        assert_eq!(highest(&[">=1, <2", "1.4", "*"]), Ok(String::from("1.4")));
+        assert_eq!(highest(&["*", "1.*"]), Ok(String::from("1.*")));
+        assert_eq!(highest(&[">=1.2", "<1.5"]).ok(), None);
+        assert!(highest(&["=1.0.3", "1.1"]).is_err());
+        assert!(highest(&["<1", "1.0"]).is_err());
+        assert!(highest(&["1.0", "latest"]).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Sub-command 'deps add', which adds a dependency to
+//! `[workspace.dependencies]` and to some of the crates.
+
+use std::path::Path;
+
+use crate::config::DepsAdd;
+use crate::package;
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &DepsAdd) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let (name, requirement) = match package::parse_dependency_spec(&cfg.dependency) {
+        Ok(spec) => spec,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut manifests = super::load_manifests(&ws);
+    let selected: Vec<usize> = cfg
+        .members
+        .iter()
+        .map(|m| super::manifest_index_or_handle_error(&ws, &manifests, m))
+        .collect();
+
+    let table = cfg.table.name();
+    for &i in &selected {
+        if manifests[i].content.dependency(table, None, name).is_some() {
+            println!(
+                "{} already depends on {}",
+                manifests[i].name.as_deref().unwrap_or_default(),
+                name
+            );
+            std::process::exit(1);
+        }
+    }
+
+    let root = &mut manifests[0].content;
+    match (root.workspace_dependency(name).is_some(), requirement) {
+        (false, None) => {
+            println!(
+                "{} is not in [workspace.dependencies] yet, so a version requirement is needed, like {}@1.0",
+                name, name
+            );
+            std::process::exit(1);
+        }
+        (false, Some(r)) => {
+            root.set_workspace_dependency(name, toml_edit::value(r));
+            println!("Added {} = \"{}\" to [workspace.dependencies]", name, r);
+        }
+        (true, Some(r)) => {
+            let changed = root
+                .workspace_dependencies_mut()
+                .and_then(|deps| deps.get_mut(name))
+                .is_some_and(|dep| super::set_requirement(dep, r));
+            if !changed {
+                println!(
+                    "{} in [workspace.dependencies] has no version requirement that could be changed",
+                    name
+                );
+                std::process::exit(1);
+            }
+            println!(
+                "Changed the requirement of {} in [workspace.dependencies] to \"{}\"",
+                name, r
+            );
+        }
+        (true, None) => {}
+    }
+
+    for &i in &selected {
+        let manifest = &mut manifests[i];
+        manifest.content.set_dependency(
+            table,
+            None,
+            name,
+            super::inherited_entry(&cfg.features, cfg.optional),
+        );
+        println!(
+            "Added {} to [{}] of {}",
+            name,
+            table,
+            manifest.name.as_deref().unwrap_or_default()
+        );
+    }
+
+    super::save_manifests(&manifests);
+}
@@ -0,0 +1,391 @@
+//! Sub-command 'deps check', which reports the dependencies whose requirements
+//! or locked versions drifted apart between the crates.
+
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use super::{Manifest, RegistryDependency};
+use crate::config::DepsCheck;
+use crate::fs::{self, IOError, Lockfile};
+use crate::workspace::Workspace;
+
+/// Name used for `[workspace.dependencies]` in place of the name of a crate.
+const WORKSPACE_DEPENDENCIES: &str = "[workspace.dependencies]";
+
+/// A package that is required with different requirements.
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct Mismatch {
+    package: String,
+
+    /// The requirements, together with the names of the crates using them.
+    requirements: Vec<(String, String)>,
+
+    /// The requirement all others can be aligned to, if they are compatible.
+    aligned: Option<String>,
+}
+
+pub fn run(cfg: &DepsCheck) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let mut manifests = super::load_manifests(&ws);
+
+    let lockfile = match fs::read_toml_file::<Lockfile>(&ws.root_path.join("Cargo.lock")) {
+        Ok(l) => Some(l),
+        Err(IOError::FsError(e)) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            println!("Reading Cargo.lock failed with error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mismatches = mismatches(&mut manifests);
+    let duplicates = duplicates(&mut manifests);
+    let locked = lockfile.as_ref().map(locked_versions).unwrap_or_default();
+
+    for m in &mismatches {
+        let requirements: Vec<String> = m
+            .requirements
+            .iter()
+            .map(|(user, r)| format!("\"{}\" ({})", r, user))
+            .collect();
+        println!(
+            "{} requirements of {}: {}",
+            match m.aligned {
+                Some(_) => "Mismatched",
+                None => "Incompatible",
+            },
+            m.package,
+            requirements.join(", ")
+        );
+    }
+    for (package, crates) in &duplicates {
+        println!(
+            "{} is specified by {} on their own, 'deps hoist' can move it into {}",
+            package,
+            crates.join(", "),
+            WORKSPACE_DEPENDENCIES
+        );
+    }
+    for (package, versions) in &locked {
+        println!(
+            "Multiple versions of {} are locked: {}",
+            package,
+            versions.join(", ")
+        );
+    }
+    if lockfile.is_none() {
+        println!("There is no Cargo.lock, so the locked versions are not checked");
+    }
+
+    if mismatches.is_empty() && duplicates.is_empty() && locked.is_empty() {
+        println!("No problems found");
+        return;
+    }
+
+    let fixable = mismatches.iter().any(|m| m.aligned.is_some());
+    if cfg.fix && fixable {
+        align(&mut manifests, &mismatches);
+        for m in &mismatches {
+            if let Some(aligned) = &m.aligned {
+                println!(
+                    "Aligned the requirements of {} to \"{}\"",
+                    m.package, aligned
+                );
+            }
+        }
+        super::save_manifests(&manifests);
+    } else if fixable {
+        println!("Run 'deps check --fix' to align the compatible requirements");
+    }
+
+    // Drifted requirements make the check fail, the other findings are only reported.
+    if mismatches.iter().any(|m| !cfg.fix || m.aligned.is_none()) {
+        std::process::exit(1);
+    }
+}
+
+/// Returns the name of the package a dependency refers to, which can differ from its key.
+fn package_name(key: &str, dependency: &RegistryDependency) -> String {
+    dependency.package.clone().unwrap_or_else(|| key.to_owned())
+}
+
+/// Collects the requirements of all packages, including the
+/// ones in `[workspace.dependencies]`, grouped by package.
+fn requirements(manifests: &mut [Manifest]) -> BTreeMap<String, Vec<(String, RegistryDependency)>> {
+    let mut requirements = BTreeMap::<String, Vec<(String, RegistryDependency)>>::new();
+
+    if let Some(deps) = manifests[0].content.workspace_dependencies_mut() {
+        for (key, dep) in deps.iter() {
+            if let Some(d) = RegistryDependency::from_item(dep) {
+                requirements
+                    .entry(package_name(key, &d))
+                    .or_default()
+                    .push((WORKSPACE_DEPENDENCIES.to_owned(), d));
+            }
+        }
+    }
+
+    for (key, user, d) in super::registry_dependencies(manifests) {
+        requirements
+            .entry(package_name(&key, &d))
+            .or_default()
+            .push((user, d));
+    }
+
+    requirements
+}
+
+/// Finds the packages that are required with different requirements.
+fn mismatches(manifests: &mut [Manifest]) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+
+    for (package, uses) in requirements(manifests) {
+        let mut distinct: Vec<(String, String)> = Vec::new();
+        for (user, d) in &uses {
+            if !distinct
+                .iter()
+                .any(|(u, r)| u == user && r == &d.requirement)
+            {
+                distinct.push((user.clone(), d.requirement.clone()));
+            }
+        }
+        if distinct.iter().all(|(_, r)| r == &distinct[0].1) {
+            continue;
+        }
+
+        let candidates: Vec<(&str, &RegistryDependency)> =
+            uses.iter().map(|(u, d)| (u.as_str(), d)).collect();
+        mismatches.push(Mismatch {
+            package,
+            requirements: distinct,
+            aligned: super::highest_requirement(&candidates).ok(),
+        });
+    }
+
+    mismatches
+}
+
+/// Finds the packages that several crates specify on their own
+/// instead of inheriting them from `[workspace.dependencies]`.
+fn duplicates(manifests: &mut [Manifest]) -> Vec<(String, Vec<String>)> {
+    requirements(manifests)
+        .into_iter()
+        .filter_map(|(package, uses)| {
+            let mut crates: Vec<String> = uses
+                .into_iter()
+                .map(|(user, _)| user)
+                .filter(|user| user != WORKSPACE_DEPENDENCIES)
+                .collect();
+            crates.dedup();
+
+            match crates.len() {
+                0 | 1 => None,
+                _ => Some((package, crates)),
+            }
+        })
+        .collect()
+}
+
+/// Finds the packages of which several versions are locked, like
+/// `rand 0.7.3` and `rand 0.8.5`. Packages of the workspace are ignored.
+fn locked_versions(lockfile: &Lockfile) -> Vec<(String, Vec<String>)> {
+    let mut versions = BTreeMap::<&str, Vec<&str>>::new();
+    for package in lockfile.packages.iter().filter(|p| p.source.is_some()) {
+        versions
+            .entry(&package.name)
+            .or_default()
+            .push(&package.version);
+    }
+
+    versions
+        .into_iter()
+        .filter_map(|(name, mut versions)| {
+            versions.sort_by(|a, b| {
+                match (a.parse::<semver::Version>(), b.parse::<semver::Version>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                }
+            });
+            versions.dedup();
+
+            match versions.len() {
+                0 | 1 => None,
+                _ => Some((
+                    name.to_owned(),
+                    versions.into_iter().map(str::to_owned).collect(),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Changes all compatible requirements of the mismatched packages to the highest one.
+fn align(manifests: &mut [Manifest], mismatches: &[Mismatch]) {
+    let aligned = |key: &str, dep: &toml_edit::Item| {
+        let d = RegistryDependency::from_item(dep)?;
+        let package = package_name(key, &d);
+        mismatches
+            .iter()
+            .find(|m| m.package == package)
+            .and_then(|m| m.aligned.clone())
+    };
+
+    if let Some(deps) = manifests[0].content.workspace_dependencies_mut() {
+        for (key, dep) in deps.iter_mut() {
+            if let Some(requirement) = aligned(key.get(), dep) {
+                super::set_requirement(dep, &requirement);
+            }
+        }
+    }
+
+    for manifest in manifests.iter_mut() {
+        for (_, deps) in manifest.content.dependency_tables_mut() {
+            for (key, dep) in deps.iter_mut() {
+                if let Some(requirement) = aligned(key.get(), dep) {
+                    super::set_requirement(dep, &requirement);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::ManifestDocument;
+    use std::path::PathBuf;
+
+    fn manifest(name: Option<&str>, content: &str) -> Manifest {
+        Manifest {
+            name: name.map(str::to_owned),
+            directory: PathBuf::from(name.unwrap_or(".")),
+            content: ManifestDocument::parse(PathBuf::from("Cargo.toml"), content).unwrap(),
+            original: content.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let mut manifests = vec![
+            manifest(
+                None,
+                "[workspace]\nmembers = [\"a\", \"b\"]\n\n[workspace.dependencies]\nlog = \"0.4.14\"\n",
+            ),
+            manifest(
+                Some("a"),
+                r#"[package]
+name = "a"
+
+[dependencies]
+serde = { version = "1.0.100", features = ["derive"] }
+rand = "0.7"
+log = { workspace = true }
+
+[dev-dependencies]
+log = "0.4.17"
+"#,
+            ),
+            manifest(
+                Some("b"),
+                r#"[package]
+name = "b"
+
+[dependencies]
+serde_json = "1.0"
+rand = "0.8"
+
+[build-dependencies]
+serde1 = { version = "1.0.140", package = "serde" }
+"#,
+            ),
+        ];
+
+        let mismatches = mismatches(&mut manifests);
+        let found: Vec<(&str, Option<&str>)> = mismatches
+            .iter()
+            .map(|m| (m.package.as_str(), m.aligned.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("log", Some("0.4.17")),
+                ("rand", None),
+                ("serde", Some("1.0.140"))
+            ]
+        );
+
+        assert_eq!(
+            duplicates(&mut manifests),
+            vec![
+                (
+                    String::from("rand"),
+                    vec![String::from("a"), String::from("b")]
+                ),
+                (
+                    String::from("serde"),
+                    vec![String::from("a"), String::from("b")]
+                )
+            ]
+        );
+
+        align(&mut manifests, &mismatches);
+        assert!(manifests[0]
+            .content
+            .to_string()
+            .contains("log = \"0.4.17\"\n"));
+        assert!(manifests[1].content.to_string().contains(
+            "serde = { version = \"1.0.140\", features = [\"derive\"] }\nrand = \"0.7\"\n"
+        ));
+        assert!(manifests[2].content.to_string().contains("rand = \"0.8\""));
+    }
+
+    #[test]
+    fn test_locked_versions() {
+        let lockfile: Lockfile = toml::from_str(
+            r#"
+version = 3
+
+[[package]]
+name = "a"
+version = "0.1.0"
+
+[[package]]
+name = "a"
+version = "0.2.0"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.10.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.140"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            locked_versions(&lockfile),
+            vec![(
+                String::from("rand"),
+                vec![
+                    String::from("0.7.3"),
+                    String::from("0.8.5"),
+                    String::from("0.10.0")
+                ]
+            )]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Sub-command 'deps hoist', which moves the dependencies used by
+//! several crates into `[workspace.dependencies]`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{Manifest, RegistryDependency};
+use crate::config::DepsHoist;
+use crate::fs::cargo_toml;
+use crate::workspace::Workspace;
+
+/// A dependency that is moved into `[workspace.dependencies]`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct Hoist {
+    key: String,
+
+    /// The entry in `[workspace.dependencies]`, with the highest requirement of all crates.
+    dependency: RegistryDependency,
+
+    /// Whether `[workspace.dependencies]` has to be changed, because the
+    /// entry is new or its requirement is raised.
+    update_workspace: bool,
+
+    /// Names of the crates that use the dependency.
+    crates: Vec<String>,
+}
+
+pub fn run(cfg: &DepsHoist) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let mut manifests = super::load_manifests(&ws);
+
+    let (hoists, skipped) = plan(&mut manifests);
+    for message in &skipped {
+        println!("{}", message);
+    }
+    for hoist in &hoists {
+        println!(
+            "{} = \"{}\" ({})",
+            hoist.key,
+            hoist.dependency.requirement,
+            hoist.crates.join(", ")
+        );
+    }
+
+    if hoists.is_empty() {
+        println!("No dependencies can be moved into [workspace.dependencies]");
+        return;
+    }
+    if cfg.dry_run {
+        println!("This was a dry run, nothing has been changed");
+        return;
+    }
+
+    apply(&mut manifests, &hoists);
+    super::save_manifests(&manifests);
+}
+
+/// Finds the dependencies that can be moved into `[workspace.dependencies]`. These are
+/// the ones used by at least two crates and the ones already in `[workspace.dependencies]`
+/// that some crates still specify on their own. Also returns the reasons why
+/// other dependencies used by several crates cannot be moved.
+fn plan(manifests: &mut [Manifest]) -> (Vec<Hoist>, Vec<String>) {
+    let mut uses = BTreeMap::<String, Vec<(String, RegistryDependency)>>::new();
+    for (key, user, d) in super::registry_dependencies(manifests) {
+        uses.entry(key).or_default().push((user, d));
+    }
+
+    let mut hoists = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (key, uses) in uses {
+        let mut crates: Vec<String> = uses.iter().map(|(c, _)| c.clone()).collect();
+        crates.dedup();
+
+        let existing = manifests[0].content.workspace_dependency(&key);
+        let inherited = match existing {
+            Some(item) => match RegistryDependency::from_item(item) {
+                Some(d) => Some(d),
+                None => {
+                    skipped.push(format!(
+                        "{} was not moved: its entry in [workspace.dependencies] is not a version requirement",
+                        key
+                    ));
+                    continue;
+                }
+            },
+            None if crates.len() < 2 => continue,
+            None => None,
+        };
+
+        let mut candidates: Vec<(&str, &RegistryDependency)> =
+            uses.iter().map(|(c, d)| (c.as_str(), d)).collect();
+        if let Some(d) = &inherited {
+            candidates.push(("[workspace.dependencies]", d));
+        }
+
+        let first = candidates[0].1;
+        if let Some((user, _)) = candidates.iter().find(|(_, d)| !d.is_same_package(first)) {
+            skipped.push(format!(
+                "{} was not moved: {} uses a different package or different default features",
+                key, user
+            ));
+            continue;
+        }
+
+        match super::highest_requirement(&candidates) {
+            Ok(requirement) => {
+                hoists.push(Hoist {
+                    update_workspace: inherited.as_ref().map(|d| &d.requirement)
+                        != Some(&requirement),
+                    dependency: RegistryDependency {
+                        requirement,
+                        package: first.package.clone(),
+                        default_features: first.default_features,
+                    },
+                    key,
+                    crates,
+                });
+            }
+            Err(reason) => skipped.push(format!("{} was not moved: {}", key, reason)),
+        }
+    }
+
+    (hoists, skipped)
+}
+
+/// Writes the dependencies into `[workspace.dependencies]` of the root
+/// Cargo.toml and lets all crates inherit them.
+fn apply(manifests: &mut [Manifest], hoists: &[Hoist]) {
+    for hoist in hoists.iter().filter(|h| h.update_workspace) {
+        let root = &mut manifests[0].content;
+        let updated = root
+            .workspace_dependencies_mut()
+            .and_then(|deps| deps.get_mut(&hoist.key))
+            .is_some_and(|dep| super::set_requirement(dep, &hoist.dependency.requirement));
+        if !updated {
+            root.set_workspace_dependency(&hoist.key, hoist.dependency.workspace_entry());
+        }
+    }
+
+    for manifest in manifests.iter_mut() {
+        for (_, deps) in manifest.content.dependency_tables_mut() {
+            for (key, dep) in deps.iter_mut() {
+                if hoists.iter().any(|h| h.key == key.get())
+                    && RegistryDependency::from_item(dep).is_some()
+                {
+                    cargo_toml::inherit_dependency(dep);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::ManifestDocument;
+    use std::path::PathBuf;
+
+    fn manifest(name: Option<&str>, content: &str) -> Manifest {
+        Manifest {
+            name: name.map(str::to_owned),
+            directory: PathBuf::from(name.unwrap_or(".")),
+            content: ManifestDocument::parse(PathBuf::from("Cargo.toml"), content).unwrap(),
+            original: content.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_hoist() {
+        let mut manifests = vec![
+            manifest(
+                None,
+                "[workspace]\nmembers = [\"a\", \"b\", \"c\"]\n\n[workspace.dependencies]\nlog = \"0.4.14\"\n",
+            ),
+            manifest(
+                Some("a"),
+                r#"[package]
+name = "a"
+
+[dependencies]
+serde = { version = "1.0.100", features = ["derive"] }
+rand = "0.7"
+log = "0.4.17" # logging
+local = { path = "../local" }
+"#,
+            ),
+            manifest(
+                Some("b"),
+                r#"[package]
+name = "b"
+
+[dependencies]
+rand = "0.8"
+
+[dependencies.serde]
+version = "1.0.140"
+optional = true
+"#,
+            ),
+            manifest(
+                Some("c"),
+                "[package]\nname = \"c\"\n\n[dependencies]\nlocal = \"1.0\"\n",
+            ),
+        ];
+
+        let (hoists, skipped) = plan(&mut manifests);
+        let planned: Vec<(&str, &str, bool)> = hoists
+            .iter()
+            .map(|h| {
+                (
+                    h.key.as_str(),
+                    h.dependency.requirement.as_str(),
+                    h.update_workspace,
+                )
+            })
+            .collect();
+        assert_eq!(
+            planned,
+            vec![("log", "0.4.17", true), ("serde", "1.0.140", true)]
+        );
+        assert_eq!(
+            skipped,
+            vec!["rand was not moved: the requirements 0.7 of a and 0.8 of b are incompatible"]
+        );
+
+        apply(&mut manifests, &hoists);
+        assert_eq!(
+            manifests[0].content.to_string(),
+            "[workspace]\nmembers = [\"a\", \"b\", \"c\"]\n\n[workspace.dependencies]\nlog = \"0.4.17\"\nserde = \"1.0.140\"\n"
+        );
+        assert_eq!(
+            manifests[1].content.to_string(),
+            r#"[package]
+name = "a"
+
+[dependencies]
+serde = { workspace = true, features = ["derive"] }
+rand = "0.7"
+log = { workspace = true } # logging
+local = { path = "../local" }
+"#
+        );
+        assert_eq!(
+            manifests[2].content.to_string(),
+            r#"[package]
+name = "b"
+
+[dependencies]
+rand = "0.8"
+
+[dependencies.serde]
+workspace = true
+optional = true
+"#
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Creates unified diffs, which are used to preview changes to files.
+
+/// Number of unchanged lines shown around every change.
+const CONTEXT: usize = 3;
+
+/// A line of the diff, together with its positions in the old and new text.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Equal(usize, usize),
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+impl Line {
+    fn is_equal(&self) -> bool {
+        matches!(self, Line::Equal(..))
+    }
+
+    fn positions(&self) -> (usize, usize) {
+        match *self {
+            Line::Equal(o, n) | Line::Delete(o, n) | Line::Insert(o, n) => (o, n),
+        }
+    }
+}
+
+/// Compares the lines of two texts, using their longest common subsequence.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Line> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Delete(i, j));
+            i += 1;
+        } else {
+            lines.push(Line::Insert(i, j));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+/// Creates a unified diff between two versions of the file `path`.
+/// Returns an empty string if they are equal.
+pub fn unified(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    if lines.iter().all(Line::is_equal) {
+        return String::new();
+    }
+
+    let mut result = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut end = 0;
+
+    while let Some(offset) = lines[end..].iter().position(|l| !l.is_equal()) {
+        let first_change = end + offset;
+        let start = first_change.saturating_sub(CONTEXT).max(end);
+
+        // Changes which are separated by only a few equal lines end up in one hunk.
+        let mut last_change = first_change;
+        end = lines.len();
+        for (i, line) in lines.iter().enumerate().skip(first_change) {
+            if !line.is_equal() {
+                last_change = i;
+            } else if i - last_change > 2 * CONTEXT {
+                end = last_change + CONTEXT + 1;
+                break;
+            }
+        }
+        end = end.min(last_change + CONTEXT + 1);
+
+        let hunk = &lines[start..end];
+        let (old_start, new_start) = hunk[0].positions();
+        let old_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Insert(..)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Delete(..)))
+            .count();
+
+        result.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_count),
+            range(new_start, new_count)
+        ));
+        for line in hunk {
+            match *line {
+                Line::Equal(o, _) => result.push_str(&format!(" {}\n", old_lines[o])),
+                Line::Delete(o, _) => result.push_str(&format!("-{}\n", old_lines[o])),
+                Line::Insert(_, n) => result.push_str(&format!("+{}\n", new_lines[n])),
+            }
+        }
+    }
+
+    result
+}
+
+/// Formats the range of a hunk header, which starts at the 0-based line `start`.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+
+        assert_eq!(
+            unified("x.toml", old, new),
+            "\
+--- a/x.toml
++++ b/x.toml
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -11,3 +11,4 @@
+ k
+ l
+ m
++n
+"
+        );
+        assert_eq!(unified("x.toml", old, old), "");
+    }
+}
@@ -0,0 +1,266 @@
+//! This module invokes the execution of the 'EXEC' command.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::config::Exec;
+use crate::package::Package;
+use crate::workspace::Workspace;
+
+/// Result of running the command in one crate.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum Outcome {
+    Success,
+
+    /// The command exited with an error. Contains the exit code, if there is one.
+    Failed(Option<i32>),
+
+    /// The command could not be started.
+    Error(io::Error),
+
+    /// The command was not run, because it failed in a crate that had to run first.
+    Skipped,
+}
+
+impl Outcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Outcome::Success)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Success => String::from("ok"),
+            Outcome::Failed(Some(code)) => format!("failed (exit code {})", code),
+            Outcome::Failed(None) => String::from("failed (terminated by signal)"),
+            Outcome::Error(e) => format!("error ({})", e),
+            Outcome::Skipped => String::from("skipped"),
+        }
+    }
+}
+
+pub fn run(cfg: &Exec) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let packages = select_packages(&ws, cfg);
+
+    if packages.is_empty() {
+        println!("No crates match the given filters");
+        return;
+    }
+
+    let (packages, outcomes) = if cfg.topo {
+        run_in_waves(&ws, &packages, &cfg.command, cfg.parallel.into())
+    } else {
+        let outcomes = run_in_packages(&ws, &packages, &cfg.command, cfg.parallel.into());
+        (packages, outcomes)
+    };
+
+    print_summary(&packages, &outcomes);
+
+    if !outcomes.iter().all(Outcome::is_success) {
+        std::process::exit(1);
+    }
+}
+
+/// Returns the packages the command should be run in, based on `--filter` and `--since`.
+fn select_packages<'a>(ws: &'a Workspace, cfg: &Exec) -> Vec<&'a Package> {
+    let mut packages: Vec<&Package> = match &cfg.since {
+        Some(since) => match ws.affected_packages(since) {
+            Ok(p) => p.into_iter().map(|(p, _)| p).collect(),
+            Err(e) => {
+                println!("Finding changed crates failed with error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => ws.packages.iter().collect(),
+    };
+
+    if let Some(filter) = &cfg.filter {
+        let pattern = match glob::Pattern::new(filter) {
+            Ok(p) => p,
+            Err(e) => {
+                println!("Invalid filter {}: {}", filter, e);
+                std::process::exit(1);
+            }
+        };
+        packages.retain(|p| pattern.matches(&p.name));
+    }
+
+    packages
+}
+
+/// Runs `command` in the directories of all `packages`, with at most `parallel`
+/// commands at the same time. Returns the outcomes in the order of `packages`.
+pub fn run_in_packages(
+    ws: &Workspace,
+    packages: &[&Package],
+    command: &[String],
+    parallel: usize,
+) -> Vec<Outcome> {
+    let width = packages.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    run_with_prefix_width(ws, packages, command, parallel, width)
+}
+
+/// Does the work of [run_in_packages], with the names in the prefixes padded to `width`.
+fn run_with_prefix_width(
+    ws: &Workspace,
+    packages: &[&Package],
+    command: &[String],
+    parallel: usize,
+    width: usize,
+) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::new());
+
+    thread::scope(|s| {
+        for _ in 0..parallel.min(packages.len()) {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let package = match packages.get(i) {
+                    Some(p) => p,
+                    None => break,
+                };
+
+                let prefix = format!("[{:width$}] ", package.name, width = width);
+                let outcome = run_in_directory(&ws.package_path(package), command, &prefix);
+                outcomes.lock().unwrap().push((i, outcome));
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(i, _)| *i);
+    outcomes.into_iter().map(|(_, o)| o).collect()
+}
+
+/// Runs `command` in the directories of all `packages` in the order of their dependencies.
+/// The waves of [DependencyGraph::waves](crate::graph::DependencyGraph::waves) are run one
+/// after another, and once the command failed, the remaining waves are skipped.
+/// Returns the packages in the order they were run in, together with their outcomes.
+pub fn run_in_waves<'a>(
+    ws: &'a Workspace,
+    packages: &[&Package],
+    command: &[String],
+    parallel: usize,
+) -> (Vec<&'a Package>, Vec<Outcome>) {
+    let waves = match ws.graph().waves() {
+        Ok(w) => w,
+        Err(e) => {
+            println!("The crates cannot be ordered: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let width = packages.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    let mut ordered = Vec::new();
+    let mut outcomes = Vec::new();
+    for wave in waves {
+        let wave: Vec<&Package> = wave
+            .into_iter()
+            .filter(|p| packages.iter().any(|s| s.name == p.name))
+            .collect();
+
+        if outcomes.iter().all(Outcome::is_success) {
+            outcomes.append(&mut run_with_prefix_width(
+                ws, &wave, command, parallel, width,
+            ));
+        } else {
+            outcomes.extend(wave.iter().map(|_| Outcome::Skipped));
+        }
+        ordered.extend(wave);
+    }
+
+    (ordered, outcomes)
+}
+
+/// Runs `command` in `dir` and prints every line of its output behind `prefix`.
+fn run_in_directory(dir: &Path, command: &[String], prefix: &str) -> Outcome {
+    let child = Command::new(&command[0])
+        .args(&command[1..])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}{}: {}", prefix, command[0], e);
+            return Outcome::Error(e);
+        }
+    };
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    thread::scope(|s| {
+        if let Some(out) = stdout {
+            s.spawn(|| print_prefixed(out, io::stdout(), prefix));
+        }
+        if let Some(err) = stderr {
+            s.spawn(|| print_prefixed(err, io::stderr(), prefix));
+        }
+    });
+
+    match child.wait() {
+        Ok(status) if status.success() => Outcome::Success,
+        Ok(status) => Outcome::Failed(status.code()),
+        Err(e) => Outcome::Error(e),
+    }
+}
+
+/// Copies every line from `input` to `output`, with `prefix` in front of it.
+fn print_prefixed(input: impl Read, output: impl Write, prefix: &str) {
+    let mut output = output;
+    for line in BufReader::new(input).lines() {
+        match line {
+            Ok(l) => {
+                if writeln!(output, "{}{}", prefix, l).is_err() {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+/// Prints a table with the outcome for every crate.
+pub fn print_summary(packages: &[&Package], outcomes: &[Outcome]) {
+    let width = packages.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    let skipped = outcomes
+        .iter()
+        .filter(|o| matches!(o, Outcome::Skipped))
+        .count();
+    let failed = outcomes.iter().filter(|o| !o.is_success()).count() - skipped;
+
+    println!();
+    println!("Summary:");
+    for (package, outcome) in packages.iter().zip(outcomes) {
+        println!(
+            "    {:width$}  {}",
+            package.name,
+            outcome.describe(),
+            width = width
+        );
+    }
+
+    if failed == 0 {
+        println!("The command succeeded in all {} crates", outcomes.len());
+    } else if skipped == 0 {
+        println!(
+            "The command failed in {} of {} crates",
+            failed,
+            outcomes.len()
+        );
+    } else {
+        println!(
+            "The command failed in {} of {} crates, {} crates were skipped",
+            failed,
+            outcomes.len(),
+            skipped
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! This module invokes the execution of the 'EXPORT-BLUEPRINT' command.
+
+use std::path::Path;
+
+use crate::blueprint;
+use crate::config::{BlueprintFormat, ExportBlueprint};
+use crate::fs;
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &ExportBlueprint) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let format = match (&cfg.format, &cfg.file) {
+        (Some(f), _) => *f,
+        (None, Some(file)) => blueprint::format_of(Path::new(file)),
+        (None, None) => BlueprintFormat::Toml,
+    };
+
+    let data = match blueprint::to_string(&ws, format) {
+        Ok(d) => d,
+        Err(e) => {
+            println!("Creating the blueprint failed with error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match &cfg.file {
+        Some(file) => {
+            if let Err(e) = fs::write_file(Path::new(file), &data, false) {
+                println!("Writing {} failed with error: {}", file, e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", data),
+    }
+}
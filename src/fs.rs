@@ -1,14 +1,22 @@
 //! This module handles all i/o tasks.
 
+use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
 use std::io::{self, ErrorKind, Read, Write};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
-use serde::{Deserialize, Serialize};
-use toml::{self, value::Table, Value};
+use serde::Deserialize;
+use toml_edit::{Document, Item};
 
-pub use cargo_toml::{CargoToml, PackageSection, WorkspaceSection};
+pub use cargo_lock::Lockfile;
+pub use cargo_toml::{
+    CargoToml, Dependency, DependencySource, GitReference, Manifest, ManifestDependency,
+    ManifestDocument, ManifestTarget, ManifestWorkspace, ManifestWorkspacePackage, PackageSection,
+    TargetSection, WorkspacePackageSection, WorkspaceSection, WorkspaceSettings,
+};
 
+pub mod cargo_lock;
 pub mod cargo_toml;
 
 /// This enum contains all possible erros raised by the cargo_ws_manage::fs module.
@@ -17,9 +25,24 @@ pub enum IOError {
     InvalidPath,
     TomlSerError(toml::ser::Error),
     TomlDeError(toml::de::Error),
+    JsonError(serde_json::Error),
+    TomlEditError(toml_edit::TomlError),
     FsError(io::Error),
 }
 
+impl fmt::Display for IOError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IOError::InvalidPath => write!(f, "invalid path"),
+            IOError::TomlSerError(e) => write!(f, "{}", e),
+            IOError::TomlDeError(e) => write!(f, "{}", e),
+            IOError::TomlEditError(e) => write!(f, "{}", e),
+            IOError::JsonError(e) => write!(f, "{}", e),
+            IOError::FsError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
 /// Creates a directory.
 pub fn mkdir(path: &Path, recursive: bool) -> Result<(), IOError> {
     let path_str = unpack_path(path)?;
@@ -52,6 +75,55 @@ pub fn rmdir(path: &Path, recursive: bool) -> Result<(), IOError> {
     Ok(())
 }
 
+/// Moves a file or directory. The target must not exist yet.
+pub fn move_path(from: &Path, to: &Path) -> Result<(), IOError> {
+    if to.exists() {
+        return Err(IOError::FsError(io::Error::from(ErrorKind::AlreadyExists)));
+    }
+
+    if let Err(e) = fs::rename(unpack_path(from)?, unpack_path(to)?) {
+        return Err(IOError::FsError(e));
+    }
+
+    Ok(())
+}
+
+/// Overwrites several files, each given as `(path, content, original)`. All of them are
+/// written to temporary files first, which then replace the original files. If any of
+/// this fails, the temporary files are removed and the files that were already replaced
+/// get their `original` content back.
+pub fn replace_files(files: &[(PathBuf, String, String)]) -> Result<(), IOError> {
+    let temp_path = |path: &Path| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".ws-manage-tmp");
+        PathBuf::from(name)
+    };
+    let remove_temp_files = |files: &[(PathBuf, String, String)]| {
+        for (path, _, _) in files {
+            let _ = fs::remove_file(temp_path(path));
+        }
+    };
+
+    for (path, data, _) in files {
+        if let Err(e) = fs::write(temp_path(path), data) {
+            remove_temp_files(files);
+            return Err(IOError::FsError(e));
+        }
+    }
+
+    for (i, (path, _, _)) in files.iter().enumerate() {
+        if let Err(e) = fs::rename(temp_path(path), path) {
+            for (path, _, original) in &files[..i] {
+                let _ = fs::write(path, original);
+            }
+            remove_temp_files(&files[i..]);
+            return Err(IOError::FsError(e));
+        }
+    }
+
+    Ok(())
+}
+
 /// Reads a toml file and deserializes it into an object.
 pub fn read_toml_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, IOError> {
     let data = read_file(path)?;
@@ -63,6 +135,15 @@ pub fn read_toml_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, IOEr
     }
 }
 
+/// Reads a json file and deserializes it into an object.
+pub fn read_json_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, IOError> {
+    let data = read_file(path)?;
+    match serde_json::from_str::<T>(&data) {
+        Ok(d) => Ok(d),
+        Err(e) => Err(IOError::JsonError(e)),
+    }
+}
+
 /// Reads a file to string.
 pub fn read_file(path: &Path) -> Result<String, IOError> {
     let path_str = unpack_path(path)?;
@@ -84,23 +165,6 @@ pub fn read_file(path: &Path) -> Result<String, IOError> {
     }
 }
 
-/// Serializes an object writes a toml file.
-pub fn write_toml_file(
-    path: &Path,
-    data: &impl Serialize,
-    allow_overwrite: bool,
-) -> Result<(), IOError> {
-    let toml = match toml::to_string(&data) {
-        Ok(s) => s,
-        Err(e) => {
-            return Err(IOError::TomlSerError(e));
-        }
-    };
-
-    write_file(path, &toml, allow_overwrite)?;
-    Ok(())
-}
-
 /// Writes a file from a string.
 pub fn write_file(path: &Path, data: &String, allow_overwrite: bool) -> Result<(), IOError> {
     let path_str = unpack_path(path)?;
@@ -108,15 +172,9 @@ pub fn write_file(path: &Path, data: &String, allow_overwrite: bool) -> Result<(
     match fs::metadata(path_str) {
         Ok(m) => {
             if m.is_dir() {
-                return Err(IOError::FsError(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Is a directory",
-                )));
+                return Err(IOError::FsError(io::Error::other("Is a directory")));
             } else if m.is_symlink() {
-                return Err(IOError::FsError(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Is a symlink",
-                )));
+                return Err(IOError::FsError(io::Error::other("Is a symlink")));
             } else if m.is_file() && !allow_overwrite {
                 return Err(IOError::FsError(io::Error::from(
                     io::ErrorKind::AlreadyExists,
@@ -138,12 +196,129 @@ pub fn write_file(path: &Path, data: &String, allow_overwrite: bool) -> Result<(
         }
     };
 
-    match file.write(data.as_bytes()) {
+    match file.write_all(data.as_bytes()) {
         Ok(_) => Ok(()),
         Err(e) => Err(IOError::FsError(e)),
     }
 }
 
+/// Recursively searches a directory for files with the given extension.
+/// Directories called `target` are skipped.
+pub fn find_files(path: &Path, extension: &str) -> Result<Vec<PathBuf>, IOError> {
+    let mut files = Vec::new();
+
+    let entries = match fs::read_dir(path) {
+        Ok(e) => e,
+        Err(e) => {
+            return Err(IOError::FsError(e));
+        }
+    };
+
+    for entry in entries {
+        let entry_path = match entry {
+            Ok(e) => e.path(),
+            Err(e) => {
+                return Err(IOError::FsError(e));
+            }
+        };
+
+        if entry_path.is_dir() {
+            if entry_path.file_name() != Some(OsStr::new("target")) {
+                files.append(&mut find_files(&entry_path, extension)?);
+            }
+        } else if entry_path.extension() == Some(OsStr::new(extension)) {
+            files.push(entry_path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Recursively lists all files of a directory.
+pub fn list_files(path: &Path) -> Result<Vec<PathBuf>, IOError> {
+    let mut files = Vec::new();
+
+    let entries = match fs::read_dir(path) {
+        Ok(e) => e,
+        Err(e) => {
+            return Err(IOError::FsError(e));
+        }
+    };
+
+    for entry in entries {
+        let entry_path = match entry {
+            Ok(e) => e.path(),
+            Err(e) => {
+                return Err(IOError::FsError(e));
+            }
+        };
+
+        if entry_path.is_dir() {
+            files.append(&mut list_files(&entry_path)?);
+        } else {
+            files.push(entry_path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Resolves `.` and `..` components of a path without accessing the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+/// Returns the absolute, canonical form of a path.
+pub fn absolute_path(path: &Path) -> Result<PathBuf, IOError> {
+    match fs::canonicalize(path) {
+        Ok(p) => Ok(p),
+        Err(e) => Err(IOError::FsError(e)),
+    }
+}
+
+/// Computes the relative path from the directory `from` to `to`.
+/// Both paths have to be relative to the same directory.
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = normalize_path(from);
+    let to = normalize_path(to);
+
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in from.components().skip(common) {
+        relative.push("..");
+    }
+    for c in to.components().skip(common) {
+        relative.push(c);
+    }
+
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+
+    relative
+}
+
 /// Unpacks the string of a path or raises an error if the path is invalid.
 fn unpack_path(path: &Path) -> Result<&str, IOError> {
     match path.to_str() {
@@ -159,20 +334,13 @@ fn unpack_path(path: &Path) -> Result<&str, IOError> {
 /// If an error occures anywhere during that process, they'll get handled by
 /// displaying an error message and exiting with code 1.
 pub fn write_cargo_toml_or_handle_error(dirname: &str, content: &CargoToml) {
-    let toml_object = gen_toml_object(content);
+    let manifest = gen_manifest(content);
     let filename = format!("{}/Cargo.toml", dirname);
 
-    match write_toml_file(Path::new(&filename), &toml_object, false) {
+    match write_file(Path::new(&filename), &manifest.to_string(), false) {
         Ok(_) => {}
         Err(e) => {
             match e {
-                IOError::TomlSerError(e) => {
-                    println!(
-                        "Writing {} failed while processing toml object with error: {}",
-                        filename, e
-                    );
-                    std::process::exit(1);
-                }
                 IOError::FsError(e) => {
                     println!("Writing {} failed with error: {}", filename, e);
                     std::process::exit(1);
@@ -188,53 +356,225 @@ pub fn write_cargo_toml_or_handle_error(dirname: &str, content: &CargoToml) {
     };
 }
 
-/// Generates a object that can be serialized by the [toml] library
-/// out of an [CargoToml] instance.
-fn gen_toml_object(content: &CargoToml) -> Table {
-    let mut data = Table::new();
+/// Loads the Cargo.toml in the given directory as a [ManifestDocument].
+/// Errors will be handled by displaying an error message and exiting with code 1.
+pub fn load_manifest_or_handle_error(dirname: &str) -> ManifestDocument {
+    match ManifestDocument::load(Path::new(dirname)) {
+        Ok(m) => m,
+        Err(e) => {
+            let filename = format!("{}/Cargo.toml", dirname);
+            match e {
+                IOError::TomlEditError(e) => {
+                    println!("Parsing {} failed with error: {}", filename, e);
+                }
+                IOError::FsError(e) => {
+                    println!("Reading {} failed with error: {}", filename, e);
+                }
+                _ => {
+                    println!("Path {} is invalid", filename);
+                }
+            };
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Writes a [ManifestDocument] back to its file.
+/// Errors will be handled by displaying an error message and exiting with code 1.
+pub fn save_manifest_or_handle_error(manifest: &ManifestDocument) {
+    if let Err(e) = manifest.save() {
+        match e {
+            IOError::FsError(e) => {
+                println!(
+                    "Writing {} failed with error: {}",
+                    manifest.path().display(),
+                    e
+                );
+            }
+            _ => {
+                println!("Path {} is invalid", manifest.path().display());
+            }
+        };
+        std::process::exit(1);
+    }
+}
+
+/// Generates the content of a new Cargo.toml out of an [CargoToml] instance.
+/// The sections are written in the order \[package\], \[dependencies\] and \[workspace\].
+fn gen_manifest(content: &CargoToml) -> Document {
+    let mut doc = Document::new();
 
     if let Some(p) = &content.package {
-        let mut pkg = Table::new();
-        pkg.insert(
-            String::from("name"),
-            Value::String(String::from(p.pkg_name)),
-        );
-        pkg.insert(
-            String::from("version"),
-            Value::String(String::from(p.pkg_version)),
-        );
-        pkg.insert(
-            String::from("edition"),
-            Value::String(String::from(p.pkg_edition)),
-        );
+        let mut pkg = toml_edit::Table::new();
+        pkg.insert("name", toml_edit::value(p.pkg_name));
+
+        let mut insert = |key: &'static str, value: Option<toml_edit::Value>| {
+            if p.pkg_inherited.contains(&key) {
+                let mut inherited = toml_edit::Table::new();
+                inherited.set_dotted(true);
+                inherited.insert("workspace", toml_edit::value(true));
+                pkg.insert(key, Item::Table(inherited));
+            } else if let Some(v) = value {
+                pkg.insert(key, Item::Value(v));
+            }
+        };
+        insert("version", Some(p.pkg_version.into()));
+        insert("edition", Some(p.pkg_edition.into()));
+        insert("authors", authors_value(p.pkg_authors));
+        insert("license", p.pkg_license.map(|l| l.into()));
+        insert("repository", p.pkg_repository.map(|r| r.into()));
 
-        data.insert(String::from("package"), Value::Table(pkg));
+        doc.insert("package", Item::Table(pkg));
     }
 
-    if let Some(d) = &content.dependencies {
-        let deps: Table = {
-            let mut t = Table::new();
-            for i in d.iter() {
-                let mut inner_t = Table::new();
-                inner_t.insert(String::from("path"), Value::String(i.clone()));
-                t.insert(i.clone(), Value::Table(inner_t));
+    for t in &content.targets {
+        let mut target = toml_edit::Table::new();
+        if let Some(name) = t.name {
+            target.insert("name", toml_edit::value(name));
+        }
+        if let Some(path) = t.path {
+            target.insert("path", toml_edit::value(path));
+        }
+        if let Some(harness) = t.harness {
+            target.insert("harness", toml_edit::value(harness));
+        }
+
+        // There is only one library, all other kinds of targets are arrays of tables.
+        match t.kind {
+            "lib" => {
+                doc.insert("lib", Item::Table(target));
             }
-            t
-        };
-        data.insert(String::from("dependencies"), Value::Table(deps));
+            kind => {
+                let tables = doc
+                    .entry(kind)
+                    .or_insert(Item::ArrayOfTables(toml_edit::ArrayOfTables::new()));
+                if let Some(tables) = tables.as_array_of_tables_mut() {
+                    tables.push(target);
+                }
+            }
+        }
+    }
+
+    if let Some(d) = &content.dependencies {
+        let mut deps = toml_edit::Table::new();
+        for i in d.iter() {
+            deps.insert(&i.name, toml_edit::value(i.to_value()));
+        }
+        doc.insert("dependencies", Item::Table(deps));
     }
 
     if let Some(w) = &content.workspace {
-        let ws: Table = {
-            let mut t = Table::new();
-            let members_as_values = w.members.iter().map(|i| Value::String(i.clone())).collect();
-            t.insert(String::from("members"), Value::Array(members_as_values));
-            t
+        let mut ws = toml_edit::Table::new();
+        ws.insert("resolver", toml_edit::value(w.resolver));
+        ws.insert(
+            "members",
+            toml_edit::value(w.members.iter().collect::<toml_edit::Array>()),
+        );
+
+        if let Some(p) = &w.package {
+            let mut pkg = toml_edit::Table::new();
+            let values = [
+                ("version", p.version.map(|v| v.into())),
+                ("edition", p.edition.map(|e| e.into())),
+                ("authors", authors_value(p.authors)),
+                ("license", p.license.map(|l| l.into())),
+                ("repository", p.repository.map(|r| r.into())),
+            ];
+            for (key, value) in values {
+                if let Some(v) = value {
+                    pkg.insert(key, Item::Value(v));
+                }
+            }
+            ws.insert("package", Item::Table(pkg));
+        }
+
+        let mut settings = toml_edit::Table::new();
+        if let Some(members_dir) = w.members_dir {
+            settings.insert("members-dir", toml_edit::value(members_dir));
+        }
+        if let Some(template_dir) = w.template_dir {
+            settings.insert("template-dir", toml_edit::value(template_dir));
+        }
+        if !settings.is_empty() {
+            let mut metadata = toml_edit::Table::new();
+            metadata.set_implicit(true);
+            metadata.insert("ws-manage", Item::Table(settings));
+            ws.insert("metadata", Item::Table(metadata));
+        }
+        doc.insert("workspace", Item::Table(ws));
+    }
+
+    if let Some(t) = content.template {
+        merge_tables(doc.as_table_mut(), t.as_table());
+
+        // Sections only added by the template, like [lib], are written between
+        // [package] and the dependencies, where cargo puts them as well.
+        let rank = |key: &str| match key {
+            "package" => 0,
+            "dependencies" | "dev-dependencies" | "build-dependencies" | "target" => 2,
+            "workspace" => 3,
+            _ => 1,
         };
-        data.insert(String::from("workspace"), Value::Table(ws));
+        doc.as_table_mut()
+            .sort_values_by(|a, _, b, _| rank(a.get()).cmp(&rank(b.get())));
+    }
+
+    doc
+}
+
+/// Merges the entries of `from` into `into`. Tables that exist in both are merged as
+/// well, and arrays of tables like \[\[bin\]\] get the entries with other names.
+/// All other entries of `from` replace the ones of `into`.
+fn merge_tables(into: &mut toml_edit::Table, from: &toml_edit::Table) {
+    for (key, item) in from.iter() {
+        match (into.get_mut(key), item) {
+            (Some(Item::Table(existing)), Item::Table(table)) => merge_tables(existing, table),
+            (Some(Item::ArrayOfTables(existing)), Item::ArrayOfTables(tables)) => {
+                fn name(t: &toml_edit::Table) -> Option<&str> {
+                    t.get("name").and_then(|n| n.as_str())
+                }
+                for table in tables.iter() {
+                    if existing.iter().all(|e| name(e) != name(table)) {
+                        let mut table = table.clone();
+                        reset_table(&mut table);
+                        existing.push(table);
+                    }
+                }
+            }
+            _ => {
+                let mut item = item.clone();
+                reset_table_format(&mut item);
+                into.insert(key, item);
+            }
+        }
+    }
+}
+
+/// Drops the position and the whitespace that tables had in the document they were
+/// parsed from, so they are written in order and separated by empty lines.
+fn reset_table_format(item: &mut Item) {
+    match item {
+        Item::Table(table) => reset_table(table),
+        Item::ArrayOfTables(tables) => tables.iter_mut().for_each(reset_table),
+        _ => {}
+    }
+}
+
+/// Resets the format of one table like [reset_table_format].
+fn reset_table(table: &mut toml_edit::Table) {
+    table.set_position(0);
+    *table.decor_mut() = toml_edit::Decor::default();
+    for (_, child) in table.iter_mut() {
+        reset_table_format(child);
     }
+}
 
-    data
+/// Converts the authors of a package into a toml array, if there are any.
+fn authors_value(authors: &[String]) -> Option<toml_edit::Value> {
+    match authors.is_empty() {
+        true => None,
+        false => Some(toml_edit::Value::Array(authors.iter().collect())),
+    }
 }
 
 /// Creates a directory by using [mkdir], but errors will be handled by
@@ -281,6 +621,125 @@ mod tests {
         assert_eq!("/abc/def/123/X_y_z.txt", unpack_path(path).unwrap());
         assert_eq!("/elephant/🐘.txt", unpack_path(path2).unwrap());
     }
+
+    #[test]
+    fn normalize_path_test() {
+        assert_eq!(normalize_path(Path::new("./a/b/../c/")), Path::new("a/c"));
+        assert_eq!(normalize_path(Path::new("a/../../b")), Path::new("../b"));
+    }
+
+    #[test]
+    fn relative_path_test() {
+        assert_eq!(
+            relative_path(Path::new("a"), Path::new("b")),
+            Path::new("../b")
+        );
+        assert_eq!(
+            relative_path(Path::new("crates/a"), Path::new("./crates/b/c")),
+            Path::new("../b/c")
+        );
+        assert_eq!(
+            relative_path(Path::new("."), Path::new("a")),
+            Path::new("a")
+        );
+        assert_eq!(
+            relative_path(Path::new("a/b"), Path::new(".")),
+            Path::new("../..")
+        );
+        assert_eq!(
+            relative_path(Path::new("a"), Path::new("a")),
+            Path::new(".")
+        );
+    }
+
+    #[test]
+    fn gen_manifest_test() {
+        let authors = vec![String::from("Jane")];
+        let deps = vec![Dependency::path("core", "crates/core")];
+        let members = vec![String::from("crates/*")];
+        let template: Document = "[lib]\nproc-macro = true\n\n[dependencies]\nsyn = \"2.0\"\n"
+            .parse()
+            .unwrap();
+
+        let manifest = gen_manifest(&CargoToml {
+            package: Some(PackageSection {
+                pkg_name: "demo",
+                pkg_version: "0.1.0",
+                pkg_edition: "2021",
+                pkg_authors: &authors,
+                pkg_license: Some("MIT"),
+                pkg_repository: None,
+                pkg_inherited: vec!["version", "authors"],
+            }),
+            dependencies: Some(&deps),
+            targets: vec![
+                TargetSection {
+                    kind: "bin",
+                    name: Some("admin"),
+                    path: Some("tools/admin.rs"),
+                    harness: None,
+                },
+                TargetSection {
+                    kind: "bench",
+                    name: Some("speed"),
+                    path: None,
+                    harness: Some(false),
+                },
+            ],
+            workspace: Some(WorkspaceSection {
+                members: &members,
+                resolver: "2",
+                package: Some(WorkspacePackageSection {
+                    version: Some("0.1.0"),
+                    edition: None,
+                    authors: &authors,
+                    license: None,
+                    repository: None,
+                }),
+                members_dir: Some("crates"),
+                template_dir: Some("templates"),
+            }),
+            template: Some(&template),
+        });
+
+        assert_eq!(
+            manifest.to_string(),
+            r#"[package]
+name = "demo"
+version.workspace = true
+edition = "2021"
+authors.workspace = true
+license = "MIT"
+
+[[bin]]
+name = "admin"
+path = "tools/admin.rs"
+
+[[bench]]
+name = "speed"
+harness = false
+
+[lib]
+proc-macro = true
+
+[dependencies]
+core = { path = "crates/core" }
+syn = "2.0"
+
+[workspace]
+resolver = "2"
+members = ["crates/*"]
+
+[workspace.package]
+version = "0.1.0"
+authors = ["Jane"]
+
+[workspace.metadata.ws-manage]
+members-dir = "crates"
+template-dir = "templates"
+"#
+        );
+    }
 }
 
 #[cfg(test)]
@@ -341,6 +800,40 @@ mod test_dir {
         let result = mkdir(path, true);
         assert!(result.is_ok());
     }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_replace_files(ctx: &mut TestFiles) {
+        let a = Path::new(&ctx.name).join("a.txt");
+        let b = Path::new(&ctx.name).join("b.txt");
+        fs::write(&a, "old a").unwrap();
+        fs::write(&b, "old b").unwrap();
+
+        let files = vec![
+            (a.clone(), String::from("new a"), String::from("old a")),
+            (b.clone(), String::from("new b"), String::from("old b")),
+        ];
+        assert!(replace_files(&files).is_ok());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "new b");
+
+        // A directory cannot be replaced by a file, so a.txt gets its old content back.
+        let dir = Path::new(&ctx.name).join("dir");
+        fs::create_dir(&dir).unwrap();
+        let files = vec![
+            (a.clone(), String::from("newer a"), String::from("new a")),
+            (dir, String::from("not a directory"), String::new()),
+        ];
+        assert!(replace_files(&files).is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+
+        let mut entries: Vec<String> = fs::read_dir(&ctx.name)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec!["a.txt", "b.txt", "dir"]);
+    }
 }
 
 #[cfg(test)]
@@ -387,27 +880,6 @@ xyz = 54626";
         }
     }
 
-    struct WriteTomlContext {
-        test_files: TestFiles,
-        pub filename: String,
-    }
-
-    impl TestContext for WriteTomlContext {
-        fn setup() -> WriteTomlContext {
-            let test_files = TestFiles::setup();
-            let filename = format!("{}/toml_write.toml", test_files.name);
-
-            WriteTomlContext {
-                test_files,
-                filename,
-            }
-        }
-
-        fn teardown(self) {
-            self.test_files.teardown();
-        }
-    }
-
     use super::*;
 
     #[test_context(ReadTomlContext)]
@@ -459,88 +931,4 @@ xyz = 54626";
         assert_eq!(data.section_a.xyz, 54626);
         assert_eq!(data.section_a.ghi, None);
     }
-
-    #[test_context(WriteTomlContext)]
-    #[test]
-    fn test_write_toml_file(ctx: &mut WriteTomlContext) {
-        #[derive(Serialize)]
-        struct Data {
-            hello: String,
-
-            section_a: SectionA,
-        }
-
-        #[derive(Serialize)]
-        struct SectionA {
-            abc: String,
-            xyz: u32,
-        }
-
-        let data = Data {
-            hello: String::from("world"),
-            section_a: SectionA {
-                abc: String::from("CBA"),
-                xyz: 54626,
-            },
-        };
-
-        let result = write_toml_file(Path::new(&ctx.filename), &data, false);
-
-        assert!(result.is_ok());
-
-        let mut file = fs::File::open(&ctx.filename).unwrap();
-
-        let mut buffer = String::new();
-
-        file.read_to_string(&mut buffer).unwrap();
-
-        assert_eq!(
-            buffer,
-            "hello = \"world\"\n\n[section_a]\nabc = \"CBA\"\nxyz = 54626\n"
-        );
-    }
-
-    #[test_context(WriteTomlContext)]
-    #[test]
-    fn test_write_toml_file_with_optionals(ctx: &mut WriteTomlContext) {
-        #[derive(Serialize)]
-        struct Data {
-            hello: String,
-            name: Option<String>,
-
-            section_a: SectionA,
-        }
-
-        #[derive(Serialize)]
-        struct SectionA {
-            abc: String,
-            ghi: Option<bool>,
-            xyz: u32,
-        }
-
-        let data = Data {
-            hello: String::from("world"),
-            name: None,
-            section_a: SectionA {
-                abc: String::from("CBA"),
-                ghi: Some(true),
-                xyz: 54626,
-            },
-        };
-
-        let result = write_toml_file(Path::new(&ctx.filename), &data, false);
-
-        assert!(result.is_ok());
-
-        let mut file = fs::File::open(&ctx.filename).unwrap();
-
-        let mut buffer = String::new();
-
-        file.read_to_string(&mut buffer).unwrap();
-
-        assert_eq!(
-            buffer,
-            "hello = \"world\"\n\n[section_a]\nabc = \"CBA\"\nghi = true\nxyz = 54626\n"
-        );
-    }
 }
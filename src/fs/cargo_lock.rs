@@ -0,0 +1,25 @@
+//! Structs for reading a Cargo.lock file, which is a toml file as well
+//!
+
+use serde::Deserialize;
+
+/// Content of a Cargo.lock, as far as it is needed by cargo-ws-manage.
+#[derive(Deserialize, Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Lockfile {
+    /// All packages of the dependency graph, including the ones of the workspace.
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A \[\[package\]\] entry of a Cargo.lock.
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+
+    /// Where the package comes from, like a registry or a git repository.
+    /// Packages of the workspace and other path dependencies have none.
+    pub source: Option<String>,
+}
@@ -1,28 +1,1238 @@
-//! Structs for describing the content of a Cargo.toml file
+//! Structs for describing and editing the content of a Cargo.toml file
 //!
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use toml_edit::{Array, Document, InlineTable, Item, TableLike, Value};
+
+use super::IOError;
+use crate::config::{Defaults, VersionMode};
+
 /// Used for describing the content of a Cargo.toml file.
 pub struct CargoToml<'a> {
     /// Holds an optional [PackageSection] object.
     pub package: Option<PackageSection<'a>>,
 
-    /// Holds an optional list of the crate names
-    /// of the member crates that the root crate depends on.
-    pub dependencies: Option<&'a Vec<String>>,
+    /// Holds an optional list of the crates that this crate depends on.
+    pub dependencies: Option<&'a Vec<Dependency>>,
+
+    /// Targets that cargo does not find by itself or that need settings.
+    pub targets: Vec<TargetSection<'a>>,
 
     /// Holds an optional [WorkspaceSection] object.
     pub workspace: Option<WorkspaceSection<'a>>,
+
+    /// Sections added by the template of the crate, like \[lib\], which
+    /// are merged into the ones above.
+    pub template: Option<&'a Document>,
 }
 
 /// Represents the \[workspace\] section of a Cargo.toml
 pub struct WorkspaceSection<'a> {
     pub members: &'a Vec<String>,
-}
 
+    /// Version of the dependency resolver, which has to be set explicitly
+    /// because virtual manifests have no edition to derive it from.
+    pub resolver: &'a str,
+
+    /// Package keys shared by all crates, written to \[workspace.package\].
+    pub package: Option<WorkspacePackageSection<'a>>,
+
+    /// Directory new member crates are created in, stored in
+    /// the \[workspace.metadata.ws-manage\] section.
+    pub members_dir: Option<&'a str>,
+
+    /// Directory of the user templates, stored like `members_dir`.
+    pub template_dir: Option<&'a str>,
+}
 
 /// Represents the \[package\] section of a Cargo.toml
 pub struct PackageSection<'a> {
     pub pkg_name: &'a str,
     pub pkg_version: &'a str,
     pub pkg_edition: &'a str,
+    pub pkg_authors: &'a [String],
+    pub pkg_license: Option<&'a str>,
+    pub pkg_repository: Option<&'a str>,
+
+    /// Keys like `version` that are inherited from \[workspace.package\]
+    /// instead of being set in the crate.
+    pub pkg_inherited: Vec<&'static str>,
+}
+
+/// Represents a \[lib\] section or a \[\[bin\]\], \[\[example\]\], \[\[test\]\]
+/// or \[\[bench\]\] entry of a Cargo.toml
+pub struct TargetSection<'a> {
+    /// Key of the section, like `bin`.
+    pub kind: &'static str,
+    pub name: Option<&'a str>,
+    pub path: Option<&'a str>,
+
+    /// Whether the target uses the test harness of libtest, which
+    /// can only run benchmarks on nightly.
+    pub harness: Option<bool>,
+}
+
+/// Represents the \[workspace.package\] section of a Cargo.toml
+pub struct WorkspacePackageSection<'a> {
+    pub version: Option<&'a str>,
+    pub edition: Option<&'a str>,
+    pub authors: &'a [String],
+    pub license: Option<&'a str>,
+    pub repository: Option<&'a str>,
+}
+
+/// Represents a dependency that is written to a Cargo.toml.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Dependency {
+    /// Key of the dependency, which is the name of the crate unless it is renamed.
+    pub name: String,
+
+    pub source: DependencySource,
+
+    /// Name of the package on crates.io or in the repository, if the dependency is renamed.
+    pub package: Option<String>,
+
+    pub features: Vec<String>,
+    pub optional: bool,
+    pub default_features: bool,
+}
+
+/// Where a [Dependency] comes from.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum DependencySource {
+    /// A version requirement for crates.io, or for another registry if one is given.
+    Registry {
+        version: String,
+        registry: Option<String>,
+    },
+
+    /// A crate in a directory, relative to the crate that depends on it. The version
+    /// requirement is used instead of the path when the crate is published.
+    Path {
+        path: String,
+        version: Option<String>,
+    },
+
+    /// A crate in a git repository, optionally at a branch, tag or revision.
+    Git {
+        url: String,
+        reference: Option<GitReference>,
+        version: Option<String>,
+    },
+
+    /// A dependency inherited from `[workspace.dependencies]`.
+    Workspace,
+}
+
+/// Selects the commit of a git dependency.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum GitReference {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+impl Dependency {
+    /// Creates a dependency from crates.io, a git repository or anywhere else.
+    pub fn new(name: &str, source: DependencySource) -> Dependency {
+        Dependency {
+            name: name.to_owned(),
+            source,
+            package: None,
+            features: Vec::new(),
+            optional: false,
+            default_features: true,
+        }
+    }
+
+    /// Creates a dependency on the crate at `path`, which is relative to the crate that depends on it.
+    pub fn path(name: &str, path: &str) -> Dependency {
+        Dependency::new(
+            name,
+            DependencySource::Path {
+                path: path.to_owned(),
+                version: None,
+            },
+        )
+    }
+
+    /// Creates the value of the dependency in a Cargo.toml. It is just the version
+    /// requirement if nothing else is set, like `serde = "1.0"`, or an inline table.
+    pub fn to_value(&self) -> Value {
+        let mut table = InlineTable::new();
+
+        match &self.source {
+            DependencySource::Registry { version, registry } => {
+                table.insert("version", version.as_str().into());
+                if let Some(r) = registry {
+                    table.insert("registry", r.as_str().into());
+                }
+            }
+            DependencySource::Path { path, version } => {
+                table.insert("path", path.as_str().into());
+                if let Some(v) = version {
+                    table.insert("version", v.as_str().into());
+                }
+            }
+            DependencySource::Git {
+                url,
+                reference,
+                version,
+            } => {
+                table.insert("git", url.as_str().into());
+                match reference {
+                    Some(GitReference::Branch(b)) => table.insert("branch", b.as_str().into()),
+                    Some(GitReference::Tag(t)) => table.insert("tag", t.as_str().into()),
+                    Some(GitReference::Rev(r)) => table.insert("rev", r.as_str().into()),
+                    None => None,
+                };
+                if let Some(v) = version {
+                    table.insert("version", v.as_str().into());
+                }
+            }
+            DependencySource::Workspace => {
+                table.insert("workspace", true.into());
+            }
+        }
+
+        if let Some(p) = &self.package {
+            table.insert("package", p.as_str().into());
+        }
+        if !self.default_features {
+            table.insert("default-features", false.into());
+        }
+        if !self.features.is_empty() {
+            table.insert("features", self.features.iter().collect::<Array>().into());
+        }
+        if self.optional {
+            table.insert("optional", true.into());
+        }
+
+        match &self.source {
+            DependencySource::Registry {
+                version,
+                registry: None,
+            } if table.len() == 1 => version.as_str().into(),
+            _ => Value::InlineTable(table),
+        }
+    }
+}
+
+/// Content of an existing Cargo.toml, as far as it is needed by cargo-ws-manage.
+/// All other keys are ignored.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Manifest {
+    pub package: Option<ManifestPackage>,
+
+    pub lib: Option<ManifestTarget>,
+
+    #[serde(default)]
+    pub bin: Vec<ManifestTarget>,
+
+    #[serde(default)]
+    pub example: Vec<ManifestTarget>,
+
+    #[serde(default)]
+    pub test: Vec<ManifestTarget>,
+
+    #[serde(default)]
+    pub bench: Vec<ManifestTarget>,
+
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub dev_dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub build_dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub target: BTreeMap<String, ManifestPlatform>,
+
+    pub workspace: Option<ManifestWorkspace>,
+}
+
+/// The \[package\] section of an existing Cargo.toml.
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: Option<Inheritable<String>>,
+    pub edition: Option<Inheritable<String>>,
+    pub authors: Option<Inheritable<Vec<String>>>,
+    pub license: Option<Inheritable<String>>,
+    pub repository: Option<Inheritable<String>>,
+    pub publish: Option<Inheritable<Publish>>,
+}
+
+/// A \[lib\], \[\[bin\]\], \[\[example\]\], \[\[test\]\] or \[\[bench\]\] section.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestTarget {
+    pub name: Option<String>,
+    pub path: Option<String>,
+
+    #[serde(default)]
+    pub proc_macro: bool,
+}
+
+/// The dependencies of a `[target.'cfg(...)']` section.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestPlatform {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub dev_dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub build_dependencies: BTreeMap<String, ManifestDependency>,
+}
+
+/// The \[workspace\] section of an existing Cargo.toml.
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestWorkspace {
+    #[serde(default)]
+    pub members: Vec<String>,
+
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    pub package: Option<ManifestWorkspacePackage>,
+
+    /// Dependencies members can inherit with `workspace = true`.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, ManifestDependency>,
+
+    #[serde(default)]
+    pub metadata: ManifestWorkspaceMetadata,
+}
+
+/// The \[workspace.metadata\] section of an existing Cargo.toml.
+#[derive(Deserialize, Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestWorkspaceMetadata {
+    #[serde(rename = "ws-manage", default)]
+    pub ws_manage: WorkspaceSettings,
+}
+
+/// Settings of this tool for a workspace, stored in
+/// the \[workspace.metadata.ws-manage\] section of the root Cargo.toml.
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct WorkspaceSettings {
+    pub version_mode: Option<VersionMode>,
+
+    /// Defaults for new crates, which take precedence over the user configuration file.
+    #[serde(flatten)]
+    pub defaults: Defaults,
+}
+
+/// The \[workspace.package\] section of an existing Cargo.toml.
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ManifestWorkspacePackage {
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    pub publish: Option<Publish>,
+}
+
+/// A value of the \[package\] section, which is either
+/// set directly or inherited from the workspace with `{ workspace = true }`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum Inheritable<T> {
+    Value(T),
+    Workspace,
+}
+
+impl<T> Inheritable<T> {
+    /// Returns the value, or `inherited` if the value is inherited from the workspace.
+    pub fn resolve<'a>(&'a self, inherited: Option<&'a T>) -> Option<&'a T> {
+        match self {
+            Inheritable::Value(v) => Some(v),
+            Inheritable::Workspace => inherited,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Inheritable<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw<T> {
+            Value(T),
+            Workspace { workspace: bool },
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Value(v) => Ok(Inheritable::Value(v)),
+            Raw::Workspace { workspace: true } => Ok(Inheritable::Workspace),
+            // Cargo does not accept this either, since there is nothing to fall back to.
+            Raw::Workspace { workspace: false } => {
+                Err(de::Error::custom("`workspace` can only be set to true"))
+            }
+        }
+    }
+}
+
+/// The `publish` key of a \[package\] section.
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum Publish {
+    Allowed(bool),
+    Registries(Vec<String>),
+}
+
+impl Publish {
+    /// Whether the package may be published at all.
+    pub fn is_allowed(&self) -> bool {
+        match self {
+            Publish::Allowed(b) => *b,
+            Publish::Registries(r) => !r.is_empty(),
+        }
+    }
+}
+
+/// A dependency in an existing Cargo.toml.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum ManifestDependency {
+    /// A dependency given only by its version requirement, like `"1.0"`.
+    Version,
+    Detailed(DetailedManifestDependency),
+}
+
+impl<'de> Deserialize<'de> for ManifestDependency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DependencyVisitor;
+
+        impl<'de> Visitor<'de> for DependencyVisitor {
+            type Value = ManifestDependency;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a version requirement or a table")
+            }
+
+            fn visit_str<E: de::Error>(self, _: &str) -> Result<ManifestDependency, E> {
+                Ok(ManifestDependency::Version)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<ManifestDependency, A::Error> {
+                DetailedManifestDependency::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(ManifestDependency::Detailed)
+            }
+        }
+
+        deserializer.deserialize_any(DependencyVisitor)
+    }
+}
+
+impl ManifestDependency {
+    /// Returns the path of the dependency, if it has one.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ManifestDependency::Version => None,
+            ManifestDependency::Detailed(d) => d.path.as_deref(),
+        }
+    }
+
+    /// Whether the dependency is inherited from `[workspace.dependencies]`.
+    pub fn is_inherited(&self) -> bool {
+        match self {
+            ManifestDependency::Version => false,
+            ManifestDependency::Detailed(d) => d.workspace,
+        }
+    }
+}
+
+/// A dependency in an existing Cargo.toml which is specified as a table.
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct DetailedManifestDependency {
+    pub path: Option<String>,
+
+    #[serde(default)]
+    pub workspace: bool,
+}
+
+/// Tables of a Cargo.toml that can contain dependencies.
+pub const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// An existing Cargo.toml which can be edited without losing
+/// comments, the order of keys or any other formatting.
+pub struct ManifestDocument {
+    path: PathBuf,
+    document: Document,
+}
+
+impl ManifestDocument {
+    /// Parses the Cargo.toml in the given directory.
+    pub fn load(dirname: &Path) -> Result<ManifestDocument, IOError> {
+        let path = dirname.join("Cargo.toml");
+        let data = super::read_file(&path)?;
+
+        ManifestDocument::parse(path, &data)
+    }
+
+    /// Parses the content of a Cargo.toml located at `path`.
+    pub fn parse(path: PathBuf, data: &str) -> Result<ManifestDocument, IOError> {
+        match data.parse::<Document>() {
+            Ok(document) => Ok(ManifestDocument { path, document }),
+            Err(e) => Err(IOError::TomlEditError(e)),
+        }
+    }
+
+    /// Writes the document back to the file it was loaded from.
+    pub fn save(&self) -> Result<(), IOError> {
+        super::write_file(&self.path, &self.document.to_string(), true)
+    }
+
+    /// Path of the Cargo.toml.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether a value of the \[package\] section is inherited
+    /// from the workspace, like `version.workspace = true`.
+    pub fn inherits_package_value(&self, key: &str) -> bool {
+        self.document
+            .get("package")
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.get("workspace"))
+            .and_then(|w| w.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Sets a string value of the \[package\] section, keeping the comments around it.
+    pub fn set_package_value(&mut self, key: &str, value: &str) {
+        match self.document["package"][key].as_value_mut() {
+            Some(v) => set_string(v, value),
+            None => self.document["package"][key] = toml_edit::value(value),
+        }
+    }
+
+    /// Sets a string value of the \[workspace.package\] section.
+    pub fn set_workspace_package_value(&mut self, key: &str, value: &str) {
+        self.document["workspace"]["package"][key] = toml_edit::value(value);
+    }
+
+    /// Returns the entries of `[workspace].members`.
+    pub fn workspace_members(&self) -> Vec<String> {
+        self.workspace_list("members")
+    }
+
+    /// Returns the entries of a list in the \[workspace\] section, like `members` or `exclude`.
+    pub fn workspace_list(&self, key: &str) -> Vec<String> {
+        self.document
+            .get("workspace")
+            .and_then(|w| w.get(key))
+            .and_then(|m| m.as_array())
+            .map(|m| {
+                m.iter()
+                    .filter_map(|i| i.as_str())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Appends `member` to `[workspace].members`, creating the list if necessary.
+    /// Returns false if it already is a member.
+    pub fn add_workspace_member(&mut self, member: &str) -> bool {
+        self.add_to_workspace_list("members", member)
+    }
+
+    /// Appends `entry` to a list in the \[workspace\] section, like `members` or `exclude`,
+    /// creating the list if necessary. Returns false if it already contains the entry.
+    pub fn add_to_workspace_list(&mut self, key: &str, entry: &str) -> bool {
+        if self.workspace_list(key).iter().any(|e| e == entry) {
+            return false;
+        }
+
+        let list = &mut self.document["workspace"][key];
+        if list.is_none() {
+            *list = toml_edit::value(Array::new());
+        }
+
+        if let Some(l) = list.as_array_mut() {
+            l.push(entry);
+        }
+
+        true
+    }
+
+    /// Replaces `member` with `new_member` in `[workspace].members` and
+    /// `[workspace].default-members`. Returns false if it was not a member.
+    pub fn replace_workspace_member(&mut self, member: &str, new_member: &str) -> bool {
+        let mut found = false;
+
+        if let Some(ws) = self
+            .document
+            .get_mut("workspace")
+            .and_then(|w| w.as_table_like_mut())
+        {
+            for key in ["members", "default-members"] {
+                if let Some(m) = ws.get_mut(key).and_then(|m| m.as_array_mut()) {
+                    for entry in m.iter_mut() {
+                        if entry.as_str() == Some(member) {
+                            set_string(entry, new_member);
+                            found = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Removes `member` from `[workspace].members` and `[workspace].default-members`.
+    /// If it was the first entry, the new first entry takes over its leading whitespace.
+    /// Returns false if it was not a member.
+    pub fn remove_workspace_member(&mut self, member: &str) -> bool {
+        let mut found = false;
+
+        if let Some(ws) = self
+            .document
+            .get_mut("workspace")
+            .and_then(|w| w.as_table_like_mut())
+        {
+            for key in ["members", "default-members"] {
+                if let Some(m) = ws.get_mut(key).and_then(|m| m.as_array_mut()) {
+                    let len = m.len();
+                    let first_prefix = m
+                        .get(0)
+                        .filter(|v| v.as_str() == Some(member))
+                        .map(|v| v.decor().prefix().cloned().unwrap_or_default());
+
+                    m.retain(|i| i.as_str() != Some(member));
+                    found |= m.len() != len;
+
+                    if let (Some(prefix), Some(first)) = (first_prefix, m.get_mut(0)) {
+                        first.decor_mut().set_prefix(prefix);
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Adds `name = { path = "path" }` to a dependency table like
+    /// `dependencies`, creating the table if necessary.
+    pub fn set_path_dependency(&mut self, table: &str, name: &str, path: &str) {
+        let dep = Dependency::path(name, path);
+        self.set_dependency(table, None, name, toml_edit::value(dep.to_value()));
+    }
+
+    /// Sets the dependency `name` of a dependency table like `dependencies`, creating the
+    /// table if necessary. With a `target` like `cfg(unix)`, the table of that target is used.
+    pub fn set_dependency(&mut self, table: &str, target: Option<&str>, name: &str, dep: Item) {
+        let parent = match target {
+            Some(t) => {
+                let targets = &mut self.document["target"];
+                if targets.is_none() {
+                    *targets = implicit_table();
+                }
+                let target = &mut targets[t];
+                if target.is_none() {
+                    *target = implicit_table();
+                }
+                target
+            }
+            None => self.document.as_item_mut(),
+        };
+
+        let deps = &mut parent[table];
+        if deps.is_none() {
+            *deps = toml_edit::table();
+        }
+        deps[name] = dep;
+    }
+
+    /// Returns the dependency `name` of a dependency table like `dependencies`,
+    /// optionally the one of a `target` like `cfg(unix)`.
+    pub fn dependency(&self, table: &str, target: Option<&str>, name: &str) -> Option<&Item> {
+        let parent = match target {
+            Some(t) => self
+                .document
+                .get("target")
+                .and_then(|targets| targets.get(t))?,
+            None => self.document.as_item(),
+        };
+        parent.get(table).and_then(|t| t.get(name))
+    }
+
+    /// Returns the entry `name` of `[workspace.dependencies]`.
+    pub fn workspace_dependency(&self, name: &str) -> Option<&Item> {
+        self.document
+            .get("workspace")
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|d| d.get(name))
+    }
+
+    /// Sets the entry `name` of `[workspace.dependencies]`, creating the table if necessary.
+    pub fn set_workspace_dependency(&mut self, name: &str, dep: Item) {
+        let deps = &mut self.document["workspace"]["dependencies"];
+        if deps.is_none() {
+            *deps = toml_edit::table();
+        }
+        deps[name] = dep;
+    }
+
+    /// Returns the name of the library target, if it is set explicitly in the \[lib\] section.
+    pub fn lib_name(&self) -> Option<&str> {
+        self.document
+            .get("lib")
+            .and_then(|l| l.get("name"))
+            .and_then(|n| n.as_str())
+    }
+
+    /// Returns the `[workspace.dependencies]` table mutably, if there is one.
+    pub fn workspace_dependencies_mut(&mut self) -> Option<&mut dyn TableLike> {
+        self.document
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("dependencies"))
+            .and_then(|d| d.as_table_like_mut())
+    }
+
+    /// Returns all tables of the \[patch\] section mutably, like `[patch.crates-io]`.
+    pub fn patch_tables_mut(&mut self) -> Vec<&mut dyn TableLike> {
+        self.document
+            .get_mut("patch")
+            .and_then(|p| p.as_table_like_mut())
+            .map(|p| {
+                p.iter_mut()
+                    .filter_map(|(_, t)| t.as_table_like_mut())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Changes the references to the dependency `old` in the \[features\] section,
+    /// like `"old/std"` or `"dep:old"`, to `new`.
+    pub fn rename_feature_dependency(&mut self, old: &str, new: &str) {
+        let features = match self
+            .document
+            .get_mut("features")
+            .and_then(|f| f.as_table_like_mut())
+        {
+            Some(f) => f,
+            None => return,
+        };
+
+        // A plain `"old"` refers to a feature instead, if there is one with that name.
+        let old_is_feature = features.contains_key(old);
+
+        for (_, enabled) in features.iter_mut() {
+            for entry in enabled
+                .as_array_mut()
+                .into_iter()
+                .flat_map(|a| a.iter_mut())
+            {
+                let renamed = match entry.as_str() {
+                    Some(s) if s == old && !old_is_feature => new.to_owned(),
+                    Some(s) if s == format!("dep:{}", old) => format!("dep:{}", new),
+                    Some(s) => match s.split_once('/') {
+                        Some((dep, feature)) if dep == old => format!("{}/{}", new, feature),
+                        Some((dep, feature)) if dep == format!("{}?", old) => {
+                            format!("{}?/{}", new, feature)
+                        }
+                        _ => continue,
+                    },
+                    None => continue,
+                };
+                set_string(entry, &renamed);
+            }
+        }
+    }
+
+    /// Removes the references to the dependency `dep` from the \[features\] section,
+    /// like `"dep/std"`, `"dep?/std"` or `"dep:dep"`. Returns the removed entries
+    /// together with the names of the features they were part of.
+    pub fn remove_feature_dependency(&mut self, dep: &str) -> Vec<(String, String)> {
+        let features = match self
+            .document
+            .get_mut("features")
+            .and_then(|f| f.as_table_like_mut())
+        {
+            Some(f) => f,
+            None => return Vec::new(),
+        };
+
+        // A plain `"dep"` refers to a feature instead, if there is one with that name.
+        let dep_is_feature = features.contains_key(dep);
+        let references = |entry: &str| match entry.split_once('/') {
+            Some((d, _)) => d == dep || d == format!("{}?", dep),
+            None => (entry == dep && !dep_is_feature) || entry == format!("dep:{}", dep),
+        };
+
+        let mut removed = Vec::new();
+        for (feature, enabled) in features.iter_mut() {
+            let enabled = match enabled.as_array_mut() {
+                Some(a) => a,
+                None => continue,
+            };
+            let first_prefix = enabled
+                .get(0)
+                .filter(|v| v.as_str().is_some_and(references))
+                .map(|v| v.decor().prefix().cloned().unwrap_or_default());
+
+            enabled.retain(|entry| match entry.as_str() {
+                Some(s) if references(s) => {
+                    removed.push((feature.get().to_owned(), s.to_owned()));
+                    false
+                }
+                _ => true,
+            });
+
+            if let (Some(prefix), Some(first)) = (first_prefix, enabled.get_mut(0)) {
+                first.decor_mut().set_prefix(prefix);
+            }
+        }
+
+        removed
+    }
+
+    /// Returns all dependency tables mutably, including target-specific ones,
+    /// together with their names like `dev-dependencies`.
+    pub fn dependency_tables_mut(&mut self) -> Vec<(String, &mut dyn TableLike)> {
+        let mut tables = Vec::new();
+
+        for (key, item) in self.document.as_table_mut().iter_mut() {
+            if DEPENDENCY_TABLES.contains(&key.get()) {
+                if let Some(t) = item.as_table_like_mut() {
+                    tables.push((key.get().to_owned(), t));
+                }
+            } else if key.get() == "target" {
+                if let Some(targets) = item.as_table_like_mut() {
+                    for (_, target) in targets.iter_mut() {
+                        if let Some(target) = target.as_table_like_mut() {
+                            for (key, item) in target.iter_mut() {
+                                if DEPENDENCY_TABLES.contains(&key.get()) {
+                                    if let Some(t) = item.as_table_like_mut() {
+                                        tables.push((key.get().to_owned(), t));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        tables
+    }
+}
+
+/// Creates a table that is only written as part of the headers of its sub-tables,
+/// like `target` in `[target.'cfg(unix)'.dependencies]`.
+fn implicit_table() -> Item {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(true);
+    Item::Table(table)
+}
+
+/// Turns a dependency into `{ workspace = true }`, so it is inherited from
+/// `[workspace.dependencies]`. Only the keys a member can still set on its own,
+/// `features` and `optional`, are kept.
+pub fn inherit_dependency(dep: &mut Item) {
+    if let Some(v) = dep.as_value() {
+        if !v.is_inline_table() {
+            let decor = v.decor().clone();
+            let mut table = InlineTable::new();
+            *table.decor_mut() = decor;
+            *dep = toml_edit::value(table);
+        }
+    }
+
+    let table = match dep.as_table_like_mut() {
+        Some(t) => t,
+        None => return,
+    };
+
+    let keys: Vec<String> = table.iter().map(|(k, _)| k.to_owned()).collect();
+    let mut kept = Vec::new();
+    for key in keys {
+        if let Some(item) = table.remove(&key) {
+            if key == "features" || key == "optional" {
+                kept.push((key, item));
+            }
+        }
+    }
+
+    table.insert("workspace", toml_edit::value(true));
+    for (key, item) in kept {
+        table.insert(&key, item);
+    }
+}
+
+/// Replaces a value with a string, keeping the comments and whitespace around it.
+pub fn set_string(value: &mut Value, s: &str) {
+    let decor = value.decor().clone();
+    *value = s.into();
+    *value.decor_mut() = decor;
+}
+
+/// Renames the key `old` of a table to `new` without moving it to the end of the table
+/// or losing its formatting. Returns false if `old` does not exist or `new` already exists.
+pub fn rename_key(table: &mut dyn TableLike, old: &str, new: &str) -> bool {
+    if !table.contains_key(old) || table.contains_key(new) {
+        return false;
+    }
+
+    let keys: Vec<String> = table.iter().map(|(k, _)| k.to_owned()).collect();
+    let start = keys.iter().position(|k| k == old).unwrap_or_default();
+
+    // Keys are always appended, so all following keys have to be inserted again.
+    for key in &keys[start..] {
+        let decor = table.key_decor(key).cloned();
+        let item = table.remove(key).unwrap_or_default();
+        let key = if key == old { new } else { key };
+
+        table.insert(key, item);
+        if let (Some(d), Some(decor)) = (table.key_decor_mut(key), decor) {
+            *d = decor;
+        }
+    }
+
+    true
+}
+
+impl std::fmt::Display for ManifestDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "\
+# The root crate
+[package]
+name = \"demo\" # keep this comment
+version = \"0.1.0\"
+edition = \"2021\"
+
+[dependencies]
+serde = \"1.0\"
+
+[workspace]
+# all members
+members = [
+    \"foo\",
+]
+";
+
+    fn manifest() -> ManifestDocument {
+        ManifestDocument::parse(PathBuf::from("demo/Cargo.toml"), MANIFEST).unwrap()
+    }
+
+    #[test]
+    fn test_read_values() {
+        let m = manifest();
+
+        assert_eq!(m.workspace_members(), vec![String::from("foo")]);
+    }
+
+    #[test]
+    fn test_deserialize_manifest() {
+        let m: Manifest = toml::from_str(
+            "\
+[package]
+name = \"a\"
+version = { workspace = true }
+edition = \"2021\"
+
+[dependencies]
+serde = \"1.0\"
+b = { path = \"../b\" }
+c = { workspace = true }
+",
+        )
+        .unwrap();
+
+        let package = m.package.unwrap();
+        let edition = String::from("2018");
+        assert_eq!(
+            package.version.unwrap().resolve(Some(&edition)),
+            Some(&edition)
+        );
+        assert_eq!(
+            package.edition.unwrap().resolve(None).map(String::as_str),
+            Some("2021")
+        );
+        assert!(matches!(
+            m.dependencies["serde"],
+            ManifestDependency::Version
+        ));
+        assert_eq!(m.dependencies["b"].path(), Some("../b"));
+        assert!(m.dependencies["c"].is_inherited());
+
+        let error = toml::from_str::<Manifest>(
+            "[package]\nname = \"a\"\nversion = { workspace = false }\n",
+        )
+        .err()
+        .unwrap();
+        assert!(error
+            .to_string()
+            .contains("`workspace` can only be set to true"));
+    }
+
+    #[test]
+    fn test_dependency_value() {
+        let registry = |version: &str| DependencySource::Registry {
+            version: version.to_owned(),
+            registry: None,
+        };
+        assert_eq!(
+            Dependency::new("serde", registry("1.0"))
+                .to_value()
+                .to_string(),
+            "\"1.0\""
+        );
+
+        let mut d = Dependency::new("serde1", registry("1.0"));
+        d.package = Some(String::from("serde"));
+        d.default_features = false;
+        d.features = vec![String::from("derive")];
+        d.optional = true;
+        assert_eq!(
+            d.to_value().to_string(),
+            "{ version = \"1.0\", package = \"serde\", default-features = false, features = [\"derive\"], optional = true }"
+        );
+
+        let d = Dependency::new(
+            "log",
+            DependencySource::Git {
+                url: String::from("https://github.com/rust-lang/log"),
+                reference: Some(GitReference::Tag(String::from("0.4.17"))),
+                version: None,
+            },
+        );
+        assert_eq!(
+            d.to_value().to_string(),
+            "{ git = \"https://github.com/rust-lang/log\", tag = \"0.4.17\" }"
+        );
+
+        let mut d = Dependency::new("log", DependencySource::Workspace);
+        d.features = vec![String::from("std")];
+        assert_eq!(
+            d.to_value().to_string(),
+            "{ workspace = true, features = [\"std\"] }"
+        );
+    }
+
+    #[test]
+    fn test_target_dependency() {
+        let mut m = manifest();
+        let dep = Dependency::path("bar", "../bar");
+        m.set_dependency(
+            "dependencies",
+            Some("cfg(unix)"),
+            "bar",
+            toml_edit::value(dep.to_value()),
+        );
+
+        assert_eq!(
+            m.to_string(),
+            format!(
+                "{}\n[target.\"cfg(unix)\".dependencies]\nbar = {{ path = \"../bar\" }}\n",
+                MANIFEST
+            )
+        );
+        assert!(m
+            .dependency("dependencies", Some("cfg(unix)"), "bar")
+            .is_some());
+        assert!(m.dependency("dependencies", None, "bar").is_none());
+        assert!(m.dependency("dependencies", None, "serde").is_some());
+    }
+
+    #[test]
+    fn test_edit_preserves_formatting() {
+        let mut m = manifest();
+
+        assert!(m.add_workspace_member("bar"));
+        assert!(!m.add_workspace_member("foo"));
+        m.set_path_dependency("dependencies", "bar", "bar");
+
+        let result = m.to_string();
+        assert!(result
+            .starts_with("# The root crate\n[package]\nname = \"demo\" # keep this comment\n"));
+        assert!(result.contains("serde = \"1.0\"\nbar = { path = \"bar\" }\n"));
+        assert!(result.contains("# all members\nmembers = [\n    \"foo\","));
+        assert_eq!(
+            m.workspace_members(),
+            vec![String::from("foo"), String::from("bar")]
+        );
+    }
+
+    #[test]
+    fn test_remove_workspace_member() {
+        let mut m = manifest();
+
+        assert!(m.remove_workspace_member("foo"));
+        assert!(!m.remove_workspace_member("foo"));
+        assert!(m.workspace_members().is_empty());
+        assert!(m.to_string().contains("# all members\nmembers = ["));
+
+        let mut m = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/foo\", \"crates/bar\"]\ndefault-members = [\n    \"crates/foo\",\n    \"crates/bar\",\n]\n",
+        )
+        .unwrap();
+        assert!(m.remove_workspace_member("crates/foo"));
+        assert_eq!(
+            m.to_string(),
+            "[workspace]\nmembers = [\"crates/bar\"]\ndefault-members = [\n    \"crates/bar\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_key() {
+        let mut m = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[dependencies]
+a = \"1\"
+old = { path = \"../old\" } # local
+b = \"1\"
+
+[dependencies.c]
+version = \"1\"
+",
+        )
+        .unwrap();
+
+        let (_, deps) = m.dependency_tables_mut().remove(0);
+        assert!(rename_key(deps, "old", "new"));
+        assert!(!rename_key(deps, "old", "new"));
+        assert!(!rename_key(deps, "a", "b"));
+        assert_eq!(
+            m.to_string(),
+            "\
+[dependencies]
+a = \"1\"
+new = { path = \"../old\" } # local
+b = \"1\"
+
+[dependencies.c]
+version = \"1\"
+"
+        );
+    }
+
+    #[test]
+    fn test_rename_feature_dependency() {
+        let mut m = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[features]
+default = [\"old\", \"old/std\"]
+extra = [\"dep:old\", \"old?/alloc\", \"older/std\"]
+",
+        )
+        .unwrap();
+
+        m.rename_feature_dependency("old", "new");
+        assert_eq!(
+            m.to_string(),
+            "\
+[features]
+default = [\"new\", \"new/std\"]
+extra = [\"dep:new\", \"new?/alloc\", \"older/std\"]
+"
+        );
+    }
+
+    #[test]
+    fn test_remove_feature_dependency() {
+        let mut m = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[features]
+default = [\"std\", \"old/std\"]
+std = [\"old?/std\", \"older/std\"]
+extra = [\"dep:old\"]
+plain = [\"old\"]
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            m.remove_feature_dependency("old"),
+            vec![
+                (String::from("default"), String::from("old/std")),
+                (String::from("std"), String::from("old?/std")),
+                (String::from("extra"), String::from("dep:old")),
+                (String::from("plain"), String::from("old")),
+            ]
+        );
+        assert_eq!(
+            m.to_string(),
+            "\
+[features]
+default = [\"std\"]
+std = [\"older/std\"]
+extra = []
+plain = []
+"
+        );
+    }
+
+    #[test]
+    fn test_replace_workspace_member() {
+        let mut m = manifest();
+
+        assert!(m.replace_workspace_member("foo", "bar"));
+        assert!(!m.replace_workspace_member("foo", "bar"));
+        assert!(m.to_string().contains("members = [\n    \"bar\",\n]"));
+    }
+
+    #[test]
+    fn test_dependency_tables() {
+        let mut m = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[dependencies]
+a = \"1\"
+
+[target.'cfg(unix)'.dev-dependencies]
+b = \"1\"
+",
+        )
+        .unwrap();
+
+        let tables = m.dependency_tables_mut();
+        let names: Vec<&str> = tables.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["dependencies", "dev-dependencies"]);
+        for (_, t) in tables {
+            t.clear();
+        }
+        assert_eq!(
+            m.to_string(),
+            "[dependencies]\n\n[target.'cfg(unix)'.dev-dependencies]\n"
+        );
+    }
 }
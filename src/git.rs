@@ -0,0 +1,106 @@
+//! Queries a local git repository by running the `git` executable.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// This enum contains all errors that can occur when running git.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum GitError {
+    /// git could not be started.
+    Spawn(io::Error),
+
+    /// git exited with an error, which contains its output on stderr.
+    Failed(String),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::Spawn(e) => write!(f, "running git failed: {}", e),
+            GitError::Failed(e) => write!(f, "git failed: {}", e.trim()),
+        }
+    }
+}
+
+/// Runs git in `dir` and returns the lines it printed.
+fn git_lines(dir: &Path, args: &[&str]) -> Result<Vec<String>, GitError> {
+    let output = match Command::new("git").arg("-C").arg(dir).args(args).output() {
+        Ok(o) => o,
+        Err(e) => return Err(GitError::Spawn(e)),
+    };
+
+    if !output.status.success() {
+        return Err(GitError::Failed(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Returns all files below `dir` that were changed since the git reference `since`,
+/// including uncommitted and untracked files. The paths are relative to `dir`.
+pub fn changed_files(dir: &Path, since: &str) -> Result<Vec<PathBuf>, GitError> {
+    // Without rename detection, a file moved between two crates counts as changed in both.
+    let mut files = git_lines(
+        dir,
+        &[
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            since,
+            "--",
+        ],
+    )?;
+    files.append(&mut git_lines(
+        dir,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?);
+
+    files.sort();
+    files.dedup();
+
+    Ok(files.into_iter().map(PathBuf::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::context_setup::TestFiles;
+    use test_context::test_context;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let identity = ["-c", "user.name=test", "-c", "user.email=test@example.com"];
+        git_lines(dir, &[&identity[..], args].concat()).unwrap();
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_changed_files_with_moved_file(ctx: &mut TestFiles) {
+        let dir = Path::new(&ctx.name);
+        std::fs::create_dir_all(dir.join("a/src")).unwrap();
+        std::fs::create_dir_all(dir.join("b/src")).unwrap();
+        std::fs::write(dir.join("a/src/util.rs"), "pub fn util() {}\n").unwrap();
+        git(dir, &["init", "--quiet"]);
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "--quiet", "-m", "initial"]);
+
+        git(dir, &["mv", "a/src/util.rs", "b/src/util.rs"]);
+        git(dir, &["commit", "--quiet", "-m", "move"]);
+
+        assert_eq!(
+            changed_files(dir, "HEAD~1").unwrap(),
+            vec![
+                PathBuf::from("a/src/util.rs"),
+                PathBuf::from("b/src/util.rs")
+            ]
+        );
+    }
+}
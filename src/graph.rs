@@ -0,0 +1,172 @@
+//! Dependency graph over the path dependencies between the packages of a workspace.
+
+use std::fmt;
+
+use crate::package::Package;
+
+/// A cycle of packages that depend on each other.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct CycleError {
+    /// Names of the packages in the cycle. The first one is repeated at the end.
+    pub chain: Vec<String>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dependency cycle: {}", self.chain.join(" -> "))
+    }
+}
+
+/// Directed graph whose edges point from a package to the packages it depends on.
+/// Dev-dependencies are ignored, because cargo allows cycles through them.
+pub struct DependencyGraph<'a> {
+    packages: &'a [Package],
+
+    /// Indices of the dependencies of every package.
+    dependencies: Vec<Vec<usize>>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new(packages: &'a [Package]) -> DependencyGraph<'a> {
+        let dependencies = packages
+            .iter()
+            .map(|p| {
+                packages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| p.depends_on(&d.name))
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .collect();
+
+        DependencyGraph {
+            packages,
+            dependencies,
+        }
+    }
+
+    /// Groups the packages into waves, so that every package only depends on
+    /// packages of earlier waves. The packages of one wave are independent of
+    /// each other and keep their original order.
+    pub fn waves(&self) -> Result<Vec<Vec<&'a Package>>, CycleError> {
+        let mut done = vec![false; self.packages.len()];
+        let mut waves = Vec::new();
+
+        while done.contains(&false) {
+            let wave: Vec<usize> = (0..self.packages.len())
+                .filter(|&i| !done[i] && self.dependencies[i].iter().all(|&d| done[d]))
+                .collect();
+
+            if wave.is_empty() {
+                return Err(self.find_cycle(&done));
+            }
+
+            for &i in &wave {
+                done[i] = true;
+            }
+            waves.push(wave.into_iter().map(|i| &self.packages[i]).collect());
+        }
+
+        Ok(waves)
+    }
+
+    /// Returns all packages in an order in which every package comes after its dependencies.
+    pub fn topological_order(&self) -> Result<Vec<&'a Package>, CycleError> {
+        Ok(self.waves()?.into_iter().flatten().collect())
+    }
+
+    /// Finds a cycle among the packages that are not `done`. Every one of them
+    /// has a dependency that is not done either, so following those
+    /// dependencies must eventually visit a package a second time.
+    fn find_cycle(&self, done: &[bool]) -> CycleError {
+        let mut path: Vec<usize> = Vec::new();
+        let mut current = done.iter().position(|d| !d).unwrap_or(0);
+
+        while !path.contains(&current) {
+            path.push(current);
+            current = match self.dependencies[current].iter().find(|&&d| !done[d]) {
+                Some(&d) => d,
+                None => break,
+            };
+        }
+
+        let start = path.iter().position(|&i| i == current).unwrap_or(0);
+        let mut chain: Vec<String> = path[start..]
+            .iter()
+            .map(|&i| self.packages[i].name.clone())
+            .collect();
+        chain.push(self.packages[current].name.clone());
+
+        CycleError { chain }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::{DependencyKind, PackageDependency};
+    use std::path::PathBuf;
+
+    fn package(name: &str, deps: &[(&str, DependencyKind)]) -> Package {
+        Package {
+            name: name.to_owned(),
+            version: None,
+            directory: PathBuf::from(name),
+            publish: true,
+            targets: Vec::new(),
+            dependencies: deps
+                .iter()
+                .map(|(d, kind)| PackageDependency {
+                    key: (*d).to_owned(),
+                    package: (*d).to_owned(),
+                    kind: *kind,
+                    platform: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn names(waves: &[Vec<&Package>]) -> Vec<Vec<String>> {
+        waves
+            .iter()
+            .map(|w| w.iter().map(|p| p.name.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_waves() {
+        let packages = vec![
+            package("app", &[("cli", DependencyKind::Normal)]),
+            package("cli", &[("core", DependencyKind::Normal)]),
+            package("core", &[("app", DependencyKind::Dev)]),
+            package("macros", &[]),
+            package("util", &[("core", DependencyKind::Build)]),
+        ];
+
+        let waves = DependencyGraph::new(&packages).waves().unwrap();
+
+        assert_eq!(
+            names(&waves),
+            vec![
+                vec![String::from("core"), String::from("macros")],
+                vec![String::from("cli"), String::from("util")],
+                vec![String::from("app")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let packages = vec![
+            package("app", &[("a", DependencyKind::Normal)]),
+            package("a", &[("b", DependencyKind::Normal)]),
+            package("b", &[("c", DependencyKind::Build)]),
+            package("c", &[("a", DependencyKind::Normal)]),
+        ];
+
+        let err = DependencyGraph::new(&packages).waves().unwrap_err();
+
+        assert_eq!(err.to_string(), "dependency cycle: a -> b -> c -> a");
+    }
+}
@@ -1,6 +1,6 @@
 //! Handles user communication.
 
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 
 /// Defaults for an yes-or-no prompt.
 pub enum DefaultBool {
@@ -19,10 +19,7 @@ pub fn prompt_yes_no(prompt: &str, default: DefaultBool) -> Option<bool> {
 
     output_prompt(&format!("{} {} ", prompt, y_n));
 
-    let mut buffer = String::new();
-    let stdin = io::stdin();
-
-    stdin.read_line(&mut buffer).expect("Could not read input.");
+    let buffer = read_line();
 
     let lower_input = buffer.trim().to_lowercase();
 
@@ -55,13 +52,7 @@ pub fn prompt_yes_no(prompt: &str, default: DefaultBool) -> Option<bool> {
 
 /// Asks the user for a string input. It also offers using default values.
 pub fn get_string(prompt: &str, default: Option<String>, allow_empty: Option<bool>) -> String {
-    let allow_empty = if let Some(allow) = allow_empty {
-        allow
-    } else {
-        true
-    };
-
-    let stdin = io::stdin();
+    let allow_empty = allow_empty.unwrap_or(true);
 
     let mut buffer;
 
@@ -72,10 +63,7 @@ pub fn get_string(prompt: &str, default: Option<String>, allow_empty: Option<boo
             if let Some(text) = &default { text } else { "" }
         ));
 
-        buffer = String::new();
-        stdin.read_line(&mut buffer).expect("Could not read input.");
-
-        buffer = buffer.trim().to_string();
+        buffer = read_line().trim().to_string();
 
         if buffer.is_empty() && default.is_none() && !allow_empty {
             continue 'input_loop;
@@ -93,6 +81,26 @@ pub fn get_string(prompt: &str, default: Option<String>, allow_empty: Option<boo
     buffer
 }
 
+/// Checks whether the standard input is a terminal, so that questions can be asked.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Reads a line from the standard input. If the input has ended,
+/// an error message is displayed and the process exits with code 1.
+fn read_line() -> String {
+    let mut buffer = String::new();
+
+    match io::stdin().read_line(&mut buffer) {
+        Ok(0) => {
+            println!("\nUnexpected end of input");
+            std::process::exit(1);
+        }
+        Ok(_) => buffer,
+        Err(e) => panic!("Could not read input: {}", e),
+    }
+}
+
 fn output_prompt(prompt: &str) {
     print!("{}", prompt);
 
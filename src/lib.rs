@@ -10,14 +10,164 @@
 //! cargo ws-manage new demo # creates new workspace in ./demo
 //! cargo ws-manage new demo other_name # creates workspace in ./other_name
 //! ```
+//! The workspace can also be described with flags instead of answering questions:
+//! ```bash
+//! cargo ws-manage new demo --root bin --member core:lib:dep --member cli:cli_dir:bin
+//! cargo ws-manage new demo --yes # root crate with no members
+//! cargo ws-manage new demo --members-dir crates --member core --member cli:bin # crates/core and crates/cli
+//! cargo ws-manage new demo --members-dir crates --glob-members --member core # members = ["crates/*"]
+//! cargo ws-manage new demo --workspace-package --yes # crates inherit [workspace.package]
+//! cargo ws-manage new demo --root none --member core --member cli:bin:uses=core # cli depends on core
+//! cargo ws-manage new demo --root none --member model:derive --member macros:proc-macro # model re-exports model-derive
+//! cargo ws-manage new demo --root none --member app:lib:bin:bins=admin:examples=demo # bin and lib targets, src/bin/admin.rs
+//! ```
+//!
+//! ## Blueprints
+//! A blueprint is a toml or json file describing the shape of a workspace:
+//! ```toml
+//! [metadata]
+//! version = "0.1.0"
+//! license = "MIT"
+//! inherit = true # written to [workspace.package]
+//!
+//! [root]
+//! kind = "bin"
+//!
+//! [[members]]
+//! name = "core"
+//! as_dependency = true
+//!
+//! [[members]]
+//! name = "cli"
+//! directory = "cli_dir"
+//! kind = "bin"
+//! dependencies = ["core"]
+//! ```
+//! It can be used to create a new workspace and exported from an existing one:
+//! ```bash
+//! cargo ws-manage new demo --from blueprint.toml
+//! cargo ws-manage export-blueprint blueprint.json
+//! ```
+//!
+//! # Adding a crate to a workspace
+//!
+//! ```bash
+//! cargo ws-manage add [--kind <lib|bin|proc-macro>|--template <NAME>] [--template-dir <DIR>] [--derive] [--lib] [--bin|--example|--test|--bench <NAME[=PATH]>]... <CRATE_NAME> [DIR_NAME]
+//! cargo ws-manage add tool --kind bin --lib --bin extra=tools/extra.rs --example demo --test smoke --bench speed
+//! ```
+//! This can be run in any directory inside the workspace.
+//!
+//! ## Templates
+//! The files of new crates are created from the built-in templates `lib`, `bin`, `bin-lib`,
+//! `proc-macro`, `cdylib`, `test` and `bench`, or from user templates in the subdirectories
+//! of a template directory. Their files may contain the placeholders `{{crate_name}}`,
+//! `{{crate_ident}}`, `{{project_name}}`, `{{author}}` and `{{year}}`.
+//! ```bash
+//! cargo ws-manage new demo --template-dir templates --member api:template=service --member ffi:template=cdylib
+//! ```
+//!
+//! ## Defaults
+//! Defaults for the edition, version, license, authors, members directory, crate kind and
+//! template directory are read from `~/.config/cargo-ws-manage/config.toml` and from the
+//! `[workspace.metadata.ws-manage]` section of the root Cargo.toml:
+//! ```toml
+//! edition = "2018"
+//! license = "MIT"
+//! crate-kind = "bin"
+//! ```
+//! Flags take precedence over the workspace, which takes precedence over the user configuration.
+//!
+//! # Removing a crate from a workspace
 //!
-//! ## More in the future
-//! There is not much else to see currently, but these are some of the features that are to be added in the future:
+//! ```bash
+//! cargo ws-manage remove [--force] [--delete] <CRATE_NAME>
+//! ```
+//! The crate is removed from the members of the workspace and all path dependencies
+//! on it are removed from the other members, together with the entries of their
+//! `[features]` that refer to it. If another member still uses the crate
+//! in its source code, nothing is changed unless `--force` is given.
 //!
-//!- add crates
-//!- remove crates
-//!- pass cargo commands to all crates (like cargo fmt, cargo add, cargo publish, ...)
+//! # Listing the crates of a workspace
 //!
+//! ```bash
+//! cargo ws-manage list [--format <human|json|tsv>]
+//! ```
+//!
+//! # Running a command in every crate
+//!
+//! ```bash
+//! cargo ws-manage exec [--filter <PATTERN>] [--since <REF>] [--parallel <N>] [--topo] -- <COMMAND>...
+//! ```
+//! for example:
+//! ```bash
+//! cargo ws-manage exec -- cargo clippy
+//! cargo ws-manage exec --filter 'app-*' --parallel 4 -- cargo test
+//! ```
+//! The output of each crate is prefixed with its name and a summary is printed at the end.
+//! With `--topo`, the crates are run in the order of the dependencies between them.
+//!
+//! # Finding the crates affected by changes
+//!
+//! ```bash
+//! cargo ws-manage changed --since <REF> [--format <plain|json>]
+//! ```
+//! This prints the crates with changes since the git reference and all crates depending on them.
+//!
+//! # Publishing all crates of a workspace
+//!
+//! ```bash
+//! cargo ws-manage publish [--dry-run] [--registry <REGISTRY>] [--allow-dirty] [--no-verify]
+//! ```
+//! Crates with `publish = false` are skipped and path dependencies on other
+//! crates of the workspace get a version requirement before publishing,
+//! also in `[workspace.dependencies]`.
+//!
+//! # Changing the versions of crates
+//!
+//! ```bash
+//! cargo ws-manage version [--mode <lockstep|independent>] [--dry-run] <patch|minor|major|VERSION> [CRATES]...
+//! ```
+//! The version requirements of path dependencies on the changed crates are updated as well.
+//! The default mode can be set in the root Cargo.toml:
+//! ```toml
+//! [workspace.metadata.ws-manage]
+//! version-mode = "lockstep"
+//! ```
+//!
+//! # Renaming a crate
+//!
+//! ```bash
+//! cargo ws-manage rename [--move-dir] [--dry-run] [--yes] <OLD_NAME> <NEW_NAME>
+//! ```
+//! The dependencies on the crate and its paths in the source code are renamed as well.
+//! All changes are shown as a diff and have to be confirmed before they are applied.
+//!
+//! # Moving a crate to a different directory
+//!
+//! ```bash
+//! cargo ws-manage mv [--dry-run] [--yes] <CRATE_NAME> <NEW_DIR>
+//! ```
+//! The members of the workspace and all relative paths to and from the crate are updated.
+//!
+//! # Sharing dependencies between crates
+//!
+//! ```bash
+//! cargo ws-manage deps hoist [--dry-run]
+//! cargo ws-manage deps add [--dev|--build] [--features <FEATURES>] [--optional] --members <CRATES> <DEP[@VERSION]>
+//! cargo ws-manage deps check [--fix]
+//! ```
+//! `hoist` moves dependencies used by several crates into `[workspace.dependencies]`,
+//! using the highest of their compatible requirements. `check` reports different
+//! requirements of the same package and multiple locked versions in the Cargo.lock.
+//!
+//! # Adding a dependency to one crate
+//!
+//! ```bash
+//! cargo ws-manage dep add [--path <DIR>|--git <URL> [--branch|--tag|--rev <REF>]|--registry <NAME>]
+//!     [--dev|--build] [--target <TARGET>] [--features <FEATURES>] [--no-default-features]
+//!     [--optional] [--rename <NAME>] <CRATE> <DEP[@VERSION]>
+//! ```
+//! Without a version, path or git repository, the dependency is inherited from `[workspace.dependencies]`.
 
 use config::{Commands, Config};
 
@@ -25,12 +175,44 @@ use config::{Commands, Config};
 pub fn run(cfg: Config) {
     match &cfg.command {
         Commands::New(new_cfg) => new::run(new_cfg),
+        Commands::Add(add_cfg) => add::run(add_cfg),
+        Commands::Remove(remove_cfg) => remove::run(remove_cfg),
+        Commands::ExportBlueprint(export_cfg) => export_blueprint::run(export_cfg),
+        Commands::List(list_cfg) => list::run(list_cfg),
+        Commands::Exec(exec_cfg) => exec::run(exec_cfg),
+        Commands::Publish(publish_cfg) => publish::run(publish_cfg),
+        Commands::Version(version_cfg) => version::run(version_cfg),
+        Commands::Changed(changed_cfg) => changed::run(changed_cfg),
+        Commands::Rename(rename_cfg) => rename::run(rename_cfg),
+        Commands::Mv(mv_cfg) => mv::run(mv_cfg),
+        Commands::Deps(deps_cfg) => deps::run(deps_cfg),
+        Commands::Dep(dep_cfg) => dep::run(dep_cfg),
     }
 }
 
+mod add;
+mod blueprint;
+mod changed;
 pub mod config;
 mod crates;
+mod dep;
+mod deps;
+mod diff;
+mod exec;
+mod export_blueprint;
 mod fs;
+mod git;
+mod graph;
 mod input;
+mod list;
+mod mv;
 mod new;
+mod package;
+mod publish;
+mod relocation;
+mod remove;
+mod rename;
+mod sources;
+mod template;
+mod version;
 mod workspace;
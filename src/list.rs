@@ -0,0 +1,175 @@
+//! This module invokes the execution of the 'LIST' command.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::{List, ListFormat};
+use crate::package::Package;
+use crate::workspace::Workspace;
+
+/// A row of the output of the 'list' command.
+#[derive(Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct ListEntry {
+    name: String,
+    version: Option<String>,
+    directory: String,
+    kind: &'static str,
+    publish: bool,
+    dependents: Vec<String>,
+}
+
+impl ListEntry {
+    fn new(ws: &Workspace, package: &Package) -> ListEntry {
+        ListEntry {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            directory: package.directory.to_string_lossy().into_owned(),
+            kind: match (package.is_lib(), package.is_bin()) {
+                (true, true) => "both",
+                (true, false) => "lib",
+                (false, true) => "bin",
+                (false, false) => "none",
+            },
+            publish: package.publish,
+            dependents: ws
+                .dependents(&package.name)
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+        }
+    }
+}
+
+pub fn run(cfg: &List) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let entries: Vec<ListEntry> = ws.packages.iter().map(|p| ListEntry::new(&ws, p)).collect();
+
+    match cfg.format.unwrap_or(ListFormat::Human) {
+        ListFormat::Human => print!("{}", to_table(&entries)),
+        ListFormat::Tsv => print!("{}", to_tsv(&entries)),
+        ListFormat::Json => match serde_json::to_string_pretty(&entries) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                println!("Creating the json output failed with error: {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Formats the entries as a table with aligned columns.
+fn to_table(entries: &[ListEntry]) -> String {
+    let mut rows = vec![[
+        String::from("NAME"),
+        String::from("VERSION"),
+        String::from("DIRECTORY"),
+        String::from("KIND"),
+        String::from("PUBLISH"),
+        String::from("DEPENDENTS"),
+    ]];
+    for e in entries {
+        rows.push([
+            e.name.clone(),
+            e.version.clone().unwrap_or_else(|| String::from("-")),
+            e.directory.clone(),
+            e.kind.to_owned(),
+            String::from(if e.publish { "yes" } else { "no" }),
+            if e.dependents.is_empty() {
+                String::from("-")
+            } else {
+                e.dependents.join(", ")
+            },
+        ]);
+    }
+
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        table.push_str(line.join("  ").trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+/// Formats the entries as tab-separated values with a header line.
+/// Dependents are separated by commas.
+fn to_tsv(entries: &[ListEntry]) -> String {
+    let mut tsv = String::from("name\tversion\tdirectory\tkind\tpublish\tdependents\n");
+    for e in entries {
+        tsv.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            e.name,
+            e.version.as_deref().unwrap_or(""),
+            e.directory,
+            e.kind,
+            e.publish,
+            e.dependents.join(",")
+        ));
+    }
+
+    tsv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<ListEntry> {
+        vec![
+            ListEntry {
+                name: String::from("demo"),
+                version: Some(String::from("0.1.0")),
+                directory: String::from("."),
+                kind: "bin",
+                publish: false,
+                dependents: Vec::new(),
+            },
+            ListEntry {
+                name: String::from("core"),
+                version: None,
+                directory: String::from("crates/core"),
+                kind: "lib",
+                publish: true,
+                dependents: vec![String::from("demo"), String::from("cli")],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_to_table() {
+        assert_eq!(
+            to_table(&entries()),
+            "\
+NAME  VERSION  DIRECTORY    KIND  PUBLISH  DEPENDENTS
+demo  0.1.0    .            bin   no       -
+core  -        crates/core  lib   yes      demo, cli
+"
+        );
+    }
+
+    #[test]
+    fn test_to_tsv() {
+        assert_eq!(
+            to_tsv(&entries()),
+            "\
+name\tversion\tdirectory\tkind\tpublish\tdependents
+demo\t0.1.0\t.\tbin\tfalse\t
+core\t\tcrates/core\tlib\ttrue\tdemo,cli
+"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use clap::Parser;
 fn main() {
     // Get command line arguments and if first element is "ws-manage", remove it
     let mut args: Vec<String> = std::env::args().skip(1).collect();
-    if args.get(0).map(|s| s.as_str()) == Some("ws-manage") {
+    if args.first().map(|s| s.as_str()) == Some("ws-manage") {
         args.remove(0);
     }
 
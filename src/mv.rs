@@ -0,0 +1,83 @@
+//! This module invokes the execution of the 'MV' command.
+
+use std::path::Path;
+
+use crate::config::Mv;
+use crate::fs;
+use crate::relocation::{self, Relocation};
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &Mv) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let package = match ws.package(&cfg.crate_name) {
+        Some(p) if p.directory == Path::new(".") => {
+            println!(
+                "{} is the root crate, so its directory cannot be moved",
+                cfg.crate_name
+            );
+            std::process::exit(1);
+        }
+        Some(p) => p,
+        None => {
+            println!("{} is not a member of the workspace", cfg.crate_name);
+            std::process::exit(1);
+        }
+    };
+
+    let current_dir = match fs::absolute_path(Path::new(".")) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("Reading the current directory failed with error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let old_path = ws.package_path(package);
+    let new_path = fs::normalize_path(&current_dir.join(&cfg.new_dir));
+
+    if !new_path.starts_with(&ws.root_path) || new_path == ws.root_path {
+        println!(
+            "{} is not a directory inside the workspace",
+            new_path.display()
+        );
+        std::process::exit(1);
+    }
+    if new_path.starts_with(&old_path) {
+        println!("{} cannot be moved into its own directory", cfg.crate_name);
+        std::process::exit(1);
+    }
+    if new_path.exists() {
+        println!(
+            "{} already exists",
+            relocation::display_path(&ws, &new_path).display()
+        );
+        std::process::exit(1);
+    }
+
+    let relocation = Relocation {
+        old_name: &cfg.crate_name,
+        new_name: &cfg.crate_name,
+        old_path,
+        new_path,
+    };
+
+    let changes = relocation::plan(&ws, &relocation);
+    relocation::print_changes(&ws, &relocation, &changes);
+
+    if cfg.dry_run {
+        println!("This was a dry run, nothing has been changed");
+        return;
+    }
+
+    if !cfg.yes && !relocation::confirm_changes() {
+        println!("Nothing has been changed");
+        return;
+    }
+
+    relocation::apply_or_handle_error(&ws, &relocation, changes);
+    println!(
+        "Moved {} to {}",
+        cfg.crate_name,
+        relocation::display_path(&ws, &relocation.new_path).display()
+    );
+}
@@ -1,10 +1,37 @@
 //! This module invokes the execution of the 'NEW' command.
 
-use crate::config::New;
+use crate::blueprint;
+use crate::config::{Defaults, New};
+use crate::input;
 use crate::workspace::Workspace;
 
 pub fn run(cfg: &New) {
+    if cfg.needs_user_input() && !input::is_interactive() {
+        println!("Standard input is not a terminal, so no questions can be asked.");
+        println!("Use --root and --member to describe the workspace or --yes to use the defaults.");
+        std::process::exit(1);
+    }
+
+    let defaults = cfg.defaults().or(Defaults::load_user_or_handle_error());
     let mut ws = Workspace::new(&cfg.project_name, &cfg.directory_name);
-    ws.fill_from_user_input();
+    match &cfg.from {
+        Some(file) => ws.fill_from_blueprint(blueprint::read_or_handle_error(file)),
+        None => ws.fill_from_config(cfg, &defaults),
+    }
+    ws.metadata.apply_defaults(&defaults);
+    ws.add_derive_crates();
+
+    // Only a template directory given as flag is stored in the new workspace.
+    if let Some(dir) = &cfg.template_dir {
+        ws.set_template_dir(dir);
+    }
+    if let Err(e) = ws
+        .load_templates(defaults.template_dir.as_deref())
+        .and_then(|_| ws.validate())
+    {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+
     ws.write_to_disk();
 }
@@ -0,0 +1,324 @@
+//! Typed model of the packages of an existing workspace.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::fs::{self, Manifest, ManifestDependency, ManifestTarget, ManifestWorkspace};
+
+/// A package of an existing workspace, read from its Cargo.toml.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Package {
+    pub name: String,
+
+    /// Version of the package, if it is set (or inherited from the workspace).
+    pub version: Option<String>,
+
+    /// Directory of the package, relative to the root of the workspace.
+    pub directory: PathBuf,
+
+    /// Whether the package may be published.
+    pub publish: bool,
+
+    pub targets: Vec<Target>,
+
+    /// Dependencies on other packages of the workspace.
+    pub dependencies: Vec<PackageDependency>,
+}
+
+/// A target (library, binary, example, ...) of a package.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Target {
+    pub kind: TargetKind,
+    pub name: String,
+
+    /// Path of the root source file, relative to the directory of the package.
+    pub path: PathBuf,
+}
+
+/// Possible kinds of a [Target].
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum TargetKind {
+    Lib,
+    ProcMacro,
+    Bin,
+    Example,
+    Test,
+    Bench,
+}
+
+/// A dependency of a package on another package of the workspace.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct PackageDependency {
+    /// Key of the dependency in the Cargo.toml, which can differ from the package name.
+    pub key: String,
+
+    /// Name of the package that is depended on.
+    pub package: String,
+
+    pub kind: DependencyKind,
+
+    /// Platform of a target-specific dependency, like `cfg(unix)`.
+    pub platform: Option<String>,
+}
+
+/// The table a dependency is declared in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl Package {
+    /// Creates a package from its deserialized Cargo.toml, located in `directory`
+    /// (relative to `root_path`). Path dependencies are resolved by `resolve`, which
+    /// maps a directory relative to the workspace root to the name of a package.
+    /// Values and dependencies inherited from the workspace are taken from `workspace`.
+    /// Returns [None] if the Cargo.toml has no \[package\] section.
+    pub fn from_manifest(
+        manifest: &Manifest,
+        root_path: &Path,
+        directory: &Path,
+        workspace: Option<&ManifestWorkspace>,
+        resolve: impl Fn(&Path) -> Option<String>,
+    ) -> Option<Package> {
+        let package = manifest.package.as_ref()?;
+        let inherited = workspace.and_then(|w| w.package.as_ref());
+        let empty = BTreeMap::new();
+        let workspace_deps = workspace.map_or(&empty, |w| &w.dependencies);
+
+        let version = package
+            .version
+            .as_ref()
+            .and_then(|v| v.resolve(inherited.and_then(|i| i.version.as_ref())))
+            .cloned();
+
+        let publish = package
+            .publish
+            .as_ref()
+            .and_then(|p| p.resolve(inherited.and_then(|i| i.publish.as_ref())))
+            .is_none_or(|p| p.is_allowed());
+
+        let mut tables = vec![
+            (DependencyKind::Normal, None, &manifest.dependencies),
+            (DependencyKind::Dev, None, &manifest.dev_dependencies),
+            (DependencyKind::Build, None, &manifest.build_dependencies),
+        ];
+        for (platform, deps) in &manifest.target {
+            tables.push((DependencyKind::Normal, Some(platform), &deps.dependencies));
+            tables.push((DependencyKind::Dev, Some(platform), &deps.dev_dependencies));
+            tables.push((
+                DependencyKind::Build,
+                Some(platform),
+                &deps.build_dependencies,
+            ));
+        }
+
+        let dependencies = tables
+            .into_iter()
+            .flat_map(|(kind, platform, deps)| {
+                path_dependencies(deps, directory, workspace_deps, &resolve).map(
+                    move |(key, package)| PackageDependency {
+                        key,
+                        package,
+                        kind,
+                        platform: platform.cloned(),
+                    },
+                )
+            })
+            .collect();
+
+        Some(Package {
+            name: package.name.clone(),
+            version,
+            directory: directory.to_path_buf(),
+            publish,
+            targets: discover_targets(manifest, &package.name, &root_path.join(directory)),
+            dependencies,
+        })
+    }
+
+    /// Whether the package has a library target.
+    pub fn is_lib(&self) -> bool {
+        self.targets
+            .iter()
+            .any(|t| matches!(t.kind, TargetKind::Lib | TargetKind::ProcMacro))
+    }
+
+    /// Whether the package has a binary target.
+    pub fn is_bin(&self) -> bool {
+        self.targets.iter().any(|t| t.kind == TargetKind::Bin)
+    }
+
+    /// Whether the package depends on the package `name`, ignoring dev-dependencies.
+    pub fn depends_on(&self, name: &str) -> bool {
+        self.dependencies
+            .iter()
+            .any(|d| d.package == name && d.kind != DependencyKind::Dev)
+    }
+}
+
+/// Checks whether `name` can be used as the name of a package. It may only contain
+/// letters, digits, `-` and `_`, and must not start with a digit or `-`.
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Splits a dependency given like `serde` or `serde@1.0` into the name and the version
+/// requirement, and checks both.
+pub fn parse_dependency_spec(spec: &str) -> Result<(&str, Option<&str>), String> {
+    let (name, requirement) = match spec.split_once('@') {
+        Some((n, r)) => (n, Some(r.trim())),
+        None => (spec, None),
+    };
+    if !is_valid_name(name) {
+        return Err(format!("{} is not a valid crate name", name));
+    }
+    if let Some(Err(e)) = requirement.map(semver::VersionReq::parse) {
+        return Err(format!(
+            "{} is not a valid version requirement: {}",
+            requirement.unwrap_or_default(),
+            e
+        ));
+    }
+
+    Ok((name, requirement))
+}
+
+/// Yields the keys of all path dependencies that point to a package
+/// of the workspace, together with the name of that package. Dependencies
+/// with `workspace = true` use the path of their entry in `workspace_deps`,
+/// which is relative to the root of the workspace.
+fn path_dependencies<'a>(
+    deps: &'a BTreeMap<String, ManifestDependency>,
+    directory: &'a Path,
+    workspace_deps: &'a BTreeMap<String, ManifestDependency>,
+    resolve: &'a impl Fn(&Path) -> Option<String>,
+) -> impl Iterator<Item = (String, String)> + 'a {
+    deps.iter().filter_map(move |(key, dep)| {
+        let path = match dep.path() {
+            Some(path) => directory.join(path),
+            None if dep.is_inherited() => PathBuf::from(workspace_deps.get(key)?.path()?),
+            None => return None,
+        };
+        resolve(&fs::normalize_path(&path)).map(|package| (key.clone(), package))
+    })
+}
+
+/// Collects all targets of a package, both the ones declared in the Cargo.toml
+/// and the ones cargo discovers automatically.
+fn discover_targets(manifest: &Manifest, package_name: &str, package_path: &Path) -> Vec<Target> {
+    let mut targets = Vec::new();
+
+    match &manifest.lib {
+        Some(lib) => targets.push(Target {
+            kind: if lib.proc_macro {
+                TargetKind::ProcMacro
+            } else {
+                TargetKind::Lib
+            },
+            name: lib
+                .name
+                .clone()
+                .unwrap_or_else(|| package_name.replace('-', "_")),
+            path: PathBuf::from(lib.path.as_deref().unwrap_or("src/lib.rs")),
+        }),
+        None => {
+            if package_path.join("src/lib.rs").is_file() {
+                targets.push(Target {
+                    kind: TargetKind::Lib,
+                    name: package_name.replace('-', "_"),
+                    path: PathBuf::from("src/lib.rs"),
+                });
+            }
+        }
+    }
+
+    if package_path.join("src/main.rs").is_file() {
+        targets.push(Target {
+            kind: TargetKind::Bin,
+            name: package_name.to_owned(),
+            path: PathBuf::from("src/main.rs"),
+        });
+    }
+
+    let sections = [
+        (TargetKind::Bin, &manifest.bin, "src/bin"),
+        (TargetKind::Example, &manifest.example, "examples"),
+        (TargetKind::Test, &manifest.test, "tests"),
+        (TargetKind::Bench, &manifest.bench, "benches"),
+    ];
+
+    for (kind, declared, dir) in sections {
+        for target in declared {
+            add_declared_target(&mut targets, kind, target, dir);
+        }
+
+        for (name, path) in auto_targets(&package_path.join(dir)) {
+            if !targets.iter().any(|t| t.kind == kind && t.name == name) {
+                targets.push(Target {
+                    kind,
+                    name,
+                    path: Path::new(dir).join(path),
+                });
+            }
+        }
+    }
+
+    targets
+}
+
+/// Adds a target declared in the Cargo.toml, replacing an automatically discovered one.
+fn add_declared_target(
+    targets: &mut Vec<Target>,
+    kind: TargetKind,
+    target: &ManifestTarget,
+    dir: &str,
+) {
+    let name = match &target.name {
+        Some(n) => n.clone(),
+        None => return,
+    };
+
+    let path = match &target.path {
+        Some(p) => PathBuf::from(p),
+        None => Path::new(dir).join(format!("{}.rs", name)),
+    };
+
+    targets.retain(|t| !(t.kind == kind && (t.name == name || t.path == path)));
+    targets.push(Target { kind, name, path });
+}
+
+/// Finds the targets in a directory like `src/bin` or `examples`, which are
+/// either single `.rs` files or directories containing a `main.rs`.
+/// Returns the name of every target and its path relative to the directory.
+fn auto_targets(dir: &Path) -> Vec<(String, PathBuf)> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut targets: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let path = e.path();
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+
+            if path.is_file() && path.extension().is_some_and(|e| e == "rs") {
+                Some((name.clone(), PathBuf::from(format!("{}.rs", name))))
+            } else if path.join("main.rs").is_file() {
+                Some((name.clone(), Path::new(&name).join("main.rs")))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    targets.sort();
+    targets
+}
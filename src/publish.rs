@@ -0,0 +1,369 @@
+//! This module invokes the execution of the 'PUBLISH' command.
+
+use std::path::{Path, PathBuf};
+
+use toml_edit::TableLike;
+
+use crate::config::Publish;
+use crate::exec::{self, Outcome};
+use crate::fs::{self, ManifestDocument};
+use crate::package::{DependencyKind, Package};
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &Publish) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let order = match ws.graph().topological_order() {
+        Ok(o) => o,
+        Err(e) => {
+            println!("The crates cannot be ordered: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let (publishable, unpublishable): (Vec<&Package>, Vec<&Package>) =
+        order.into_iter().partition(|p| p.publish);
+
+    let problems = check_publishable(&ws, &publishable);
+    if !problems.is_empty() {
+        println!("The workspace cannot be published:");
+        for problem in problems {
+            println!("    {}", problem);
+        }
+        std::process::exit(1);
+    }
+
+    if publishable.is_empty() {
+        println!("There are no crates to publish");
+        return;
+    }
+
+    let versions: Vec<(PathBuf, String)> = ws
+        .packages
+        .iter()
+        .filter_map(|p| p.version.clone().map(|v| (ws.package_path(p), v)))
+        .collect();
+
+    let mut manifests = Vec::new();
+
+    // Dependencies inherited from the workspace get their requirement in the root Cargo.toml.
+    let inherited: Vec<&str> = publishable
+        .iter()
+        .flat_map(|p| &p.dependencies)
+        .filter(|d| d.kind != DependencyKind::Dev)
+        .map(|d| d.key.as_str())
+        .collect();
+    let mut root_manifest = fs::load_manifest_or_handle_error(&ws.root_path.to_string_lossy());
+    let mut requirements = add_workspace_version_requirements(
+        &mut root_manifest,
+        &ws.root_path,
+        &versions,
+        &inherited,
+    );
+    if publishable.iter().any(|p| p.directory == Path::new(".")) {
+        requirements.append(&mut add_version_requirements(
+            &mut root_manifest,
+            &ws.root_path,
+            &versions,
+        ));
+    }
+    if !requirements.is_empty() {
+        manifests.push((PathBuf::from("Cargo.toml"), root_manifest, requirements));
+    }
+
+    for package in publishable.iter().filter(|p| p.directory != Path::new(".")) {
+        let package_path = ws.package_path(package);
+        let mut manifest = fs::load_manifest_or_handle_error(&package_path.to_string_lossy());
+        let requirements = add_version_requirements(&mut manifest, &package_path, &versions);
+        if !requirements.is_empty() {
+            manifests.push((package.directory.join("Cargo.toml"), manifest, requirements));
+        }
+    }
+
+    println!("Crates will be published in this order:");
+    for package in &publishable {
+        println!(
+            "    {} {}",
+            package.name,
+            package.version.as_deref().unwrap_or_default()
+        );
+    }
+
+    if !unpublishable.is_empty() {
+        println!("Crates that are not published because of `publish = false`:");
+        for package in &unpublishable {
+            println!("    {}", package.name);
+        }
+    }
+
+    if !manifests.is_empty() {
+        println!("Version requirements will be added to path dependencies:");
+        for (path, _, requirements) in &manifests {
+            for (key, version) in requirements {
+                println!("    {}: {} = \"{}\"", path.display(), key, version);
+            }
+        }
+    }
+
+    if cfg.dry_run {
+        println!("This was a dry run, nothing has been changed");
+        return;
+    }
+
+    for (path, manifest, _) in &manifests {
+        fs::save_manifest_or_handle_error(manifest);
+        println!("Updated {}", path.display());
+    }
+    let updated = !manifests.is_empty();
+    if updated && !cfg.allow_dirty {
+        println!("Passing --allow-dirty to cargo publish for the updated Cargo.toml files");
+    }
+
+    let command = publish_command(cfg, updated);
+    let mut outcomes: Vec<Outcome> = Vec::new();
+    for package in &publishable {
+        if outcomes.iter().all(Outcome::is_success) {
+            outcomes.append(&mut exec::run_in_packages(&ws, &[package], &command, 1));
+        } else {
+            outcomes.push(Outcome::Skipped);
+        }
+    }
+
+    exec::print_summary(&publishable, &outcomes);
+
+    if !outcomes.iter().all(Outcome::is_success) {
+        std::process::exit(1);
+    }
+}
+
+/// Checks that all packages in `publishable` have a version and do not
+/// depend on packages that cannot be published. Returns all problems found.
+fn check_publishable(ws: &Workspace, publishable: &[&Package]) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for package in publishable {
+        if package.version.is_none() {
+            problems.push(format!("{} has no version", package.name));
+        }
+
+        for dep in &package.dependencies {
+            if dep.kind == DependencyKind::Dev {
+                continue;
+            }
+
+            if ws.package(&dep.package).is_some_and(|p| !p.publish) {
+                problems.push(format!(
+                    "{} depends on {}, which has `publish = false`",
+                    package.name, dep.package
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Builds the `cargo publish` command with the flags that are passed on. `--allow-dirty`
+/// is also passed if Cargo.toml files were `updated`, which cargo would reject otherwise.
+fn publish_command(cfg: &Publish, updated: bool) -> Vec<String> {
+    let mut command = vec![String::from("cargo"), String::from("publish")];
+
+    if let Some(registry) = &cfg.registry {
+        command.push(String::from("--registry"));
+        command.push(registry.clone());
+    }
+    if cfg.allow_dirty || updated {
+        command.push(String::from("--allow-dirty"));
+    }
+    if cfg.no_verify {
+        command.push(String::from("--no-verify"));
+    }
+
+    command
+}
+
+/// Adds a version requirement to every path dependency of a Cargo.toml that has none yet
+/// and points to one of the packages in `versions`, which maps their absolute paths to
+/// their versions. Dev-dependencies are left alone, because cargo removes path-only
+/// dev-dependencies when publishing. Returns the keys of the changed dependencies
+/// together with the versions they now require.
+fn add_version_requirements(
+    manifest: &mut ManifestDocument,
+    manifest_dir: &Path,
+    versions: &[(PathBuf, String)],
+) -> Vec<(String, String)> {
+    let mut requirements = Vec::new();
+
+    for (table, deps) in manifest.dependency_tables_mut() {
+        if table == "dev-dependencies" {
+            continue;
+        }
+        add_to_table(deps, manifest_dir, versions, |_| true, &mut requirements);
+    }
+
+    requirements
+}
+
+/// Adds version requirements like [add_version_requirements] to the entries of
+/// `[workspace.dependencies]` in the root Cargo.toml, but only to the ones named
+/// in `inherited`, which are the keys of the dependencies of the published crates.
+fn add_workspace_version_requirements(
+    manifest: &mut ManifestDocument,
+    root_path: &Path,
+    versions: &[(PathBuf, String)],
+    inherited: &[&str],
+) -> Vec<(String, String)> {
+    let mut requirements = Vec::new();
+
+    if let Some(deps) = manifest.workspace_dependencies_mut() {
+        let selected = |key: &str| inherited.contains(&key);
+        add_to_table(deps, root_path, versions, selected, &mut requirements);
+    }
+
+    requirements
+}
+
+/// Adds the version requirements to the dependencies of one table for which `selected`
+/// is true. The changed keys and their versions are added to `requirements`.
+fn add_to_table(
+    deps: &mut dyn TableLike,
+    manifest_dir: &Path,
+    versions: &[(PathBuf, String)],
+    selected: impl Fn(&str) -> bool,
+    requirements: &mut Vec<(String, String)>,
+) {
+    for (key, dep) in deps.iter_mut() {
+        if !selected(key.get()) || dep.get("version").is_some() {
+            continue;
+        }
+
+        let path = match dep.get("path").and_then(|p| p.as_str()) {
+            Some(p) => fs::normalize_path(&manifest_dir.join(p)),
+            None => continue,
+        };
+
+        let version = match versions.iter().find(|(p, _)| *p == path) {
+            Some((_, v)) => v,
+            None => continue,
+        };
+
+        if let Some(d) = dep.as_inline_table_mut() {
+            d.insert("version", version.as_str().into());
+            // Otherwise the space in front of the closing brace stays behind the path.
+            d.fmt();
+        } else if let Some(d) = dep.as_table_like_mut() {
+            d.insert("version", toml_edit::value(version.as_str()));
+        }
+        requirements.push((key.get().to_owned(), version.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_version_requirements() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("app/Cargo.toml"),
+            "\
+[dependencies]
+core = { path = \"../core\" }
+util = { path = \"../util\", version = \"0.1\" }
+serde = \"1.0\"
+
+[dependencies.macros]
+path = \"../macros\"
+
+[dev-dependencies]
+testing = { path = \"../testing\" }
+
+[target.'cfg(unix)'.build-dependencies]
+core = { path = \"../core/\" }
+",
+        )
+        .unwrap();
+
+        let versions = vec![
+            (PathBuf::from("core"), String::from("0.2.0")),
+            (PathBuf::from("util"), String::from("0.1.3")),
+            (PathBuf::from("macros"), String::from("1.0.0")),
+            (PathBuf::from("testing"), String::from("0.1.0")),
+        ];
+
+        let requirements = add_version_requirements(&mut manifest, Path::new("./app"), &versions);
+
+        assert_eq!(
+            requirements,
+            vec![
+                (String::from("core"), String::from("0.2.0")),
+                (String::from("macros"), String::from("1.0.0")),
+                (String::from("core"), String::from("0.2.0")),
+            ]
+        );
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[dependencies]
+core = { path = \"../core\", version = \"0.2.0\" }
+util = { path = \"../util\", version = \"0.1\" }
+serde = \"1.0\"
+
+[dependencies.macros]
+path = \"../macros\"
+version = \"1.0.0\"
+
+[dev-dependencies]
+testing = { path = \"../testing\" }
+
+[target.'cfg(unix)'.build-dependencies]
+core = { path = \"../core/\", version = \"0.2.0\" }
+"
+        );
+    }
+
+    #[test]
+    fn test_add_workspace_version_requirements() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[workspace]
+members = [\"crates/*\"]
+
+[workspace.dependencies]
+core = { path = \"crates/core\" }
+testing = { path = \"crates/testing\" }
+serde = \"1.0\"
+",
+        )
+        .unwrap();
+
+        let versions = vec![
+            (PathBuf::from("crates/core"), String::from("0.2.0")),
+            (PathBuf::from("crates/testing"), String::from("0.1.0")),
+        ];
+
+        let requirements = add_workspace_version_requirements(
+            &mut manifest,
+            Path::new("."),
+            &versions,
+            &["core", "serde"],
+        );
+
+        assert_eq!(
+            requirements,
+            vec![(String::from("core"), String::from("0.2.0"))]
+        );
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[workspace]
+members = [\"crates/*\"]
+
+[workspace.dependencies]
+core = { path = \"crates/core\", version = \"0.2.0\" }
+testing = { path = \"crates/testing\" }
+serde = \"1.0\"
+"
+        );
+    }
+}
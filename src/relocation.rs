@@ -0,0 +1,580 @@
+//! Renames and moves packages, and updates everything in the workspace that refers to them.
+
+use std::path::{Path, PathBuf};
+
+use toml_edit::TableLike;
+
+use crate::diff;
+use crate::fs::{self, cargo_toml, ManifestDocument};
+use crate::input;
+use crate::sources;
+use crate::workspace::{self, Workspace};
+
+/// A file that is changed by a relocation.
+pub struct FileChange {
+    /// Absolute path of the file, before the directory of the package is moved.
+    /// The change is written to its new location.
+    path: PathBuf,
+    original: String,
+    content: String,
+}
+
+/// A package that gets a new name and/or a new directory, which are both
+/// given as absolute paths.
+pub struct Relocation<'a> {
+    pub old_name: &'a str,
+    pub new_name: &'a str,
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+}
+
+impl Relocation<'_> {
+    /// Returns where the absolute `path` is located once the package has been moved.
+    fn moved(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.old_path) {
+            Ok(rest) if rest.as_os_str().is_empty() => self.new_path.clone(),
+            Ok(rest) => self.new_path.join(rest),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    /// Whether the directory of the package changes.
+    fn is_move(&self) -> bool {
+        self.old_path != self.new_path
+    }
+}
+
+/// Computes the changes to all Cargo.toml and source files of the workspace that
+/// are needed for the relocation, without writing anything.
+pub fn plan(ws: &Workspace, relocation: &Relocation) -> Vec<FileChange> {
+    let package = match ws.package(relocation.old_name) {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+
+    let mut changes = Vec::new();
+    let mut source_dirs = vec![package.directory.clone()];
+    let mut lib_name_changes = package.is_lib();
+    let mut workspace_key_renamed = false;
+
+    let mut directories = vec![PathBuf::from(".")];
+    directories.extend(ws.members().map(|p| p.directory.clone()));
+
+    for directory in directories {
+        let manifest_dir = fs::normalize_path(&ws.root_path.join(&directory));
+        let mut manifest = fs::load_manifest_or_handle_error(&manifest_dir.to_string_lossy());
+        let original = manifest.to_string();
+
+        if directory == package.directory {
+            if relocation.old_name != relocation.new_name {
+                manifest.set_package_value("name", relocation.new_name);
+            }
+            lib_name_changes &= manifest.lib_name().is_none();
+        }
+
+        if directory == Path::new(".") {
+            workspace_key_renamed =
+                update_root_manifest(&mut manifest, &ws.root_path, &package.directory, relocation);
+        }
+
+        let new_manifest_dir = relocation.moved(&manifest_dir);
+        let mut key_renamed = false;
+        for (_, deps) in manifest.dependency_tables_mut() {
+            key_renamed |= update_dependencies(
+                deps,
+                &manifest_dir,
+                &new_manifest_dir,
+                relocation,
+                workspace_key_renamed,
+            );
+        }
+
+        if key_renamed {
+            manifest.rename_feature_dependency(relocation.old_name, relocation.new_name);
+            if !source_dirs.contains(&directory) {
+                source_dirs.push(directory);
+            }
+        }
+
+        let content = manifest.to_string();
+        if content != original {
+            changes.push(FileChange {
+                path: manifest.path().to_path_buf(),
+                original,
+                content,
+            });
+        }
+    }
+
+    // Sources refer to the library by its name, unless the dependency is renamed
+    // with `package = ...` or the library has a name of its own.
+    let old_ident = relocation.old_name.replace('-', "_");
+    let new_ident = relocation.new_name.replace('-', "_");
+    if lib_name_changes && old_ident != new_ident {
+        for directory in &source_dirs {
+            changes.append(&mut rename_in_sources(
+                &ws.root_path.join(directory),
+                &old_ident,
+                &new_ident,
+            ));
+        }
+    }
+
+    changes
+}
+
+/// Prints the planned changes as a diff.
+pub fn print_changes(ws: &Workspace, relocation: &Relocation, changes: &[FileChange]) {
+    for change in changes {
+        print!(
+            "{}",
+            diff::unified(
+                &display_path(ws, &change.path).to_string_lossy(),
+                &change.original,
+                &change.content
+            )
+        );
+    }
+
+    if relocation.is_move() {
+        println!(
+            "Move {} -> {}",
+            display_path(ws, &relocation.old_path).display(),
+            display_path(ws, &relocation.new_path).display()
+        );
+    }
+}
+
+/// Moves the directory of the package and writes the planned changes, see [apply].
+/// Errors will be handled by displaying an error message and exiting with code 1.
+pub fn apply_or_handle_error(ws: &Workspace, relocation: &Relocation, changes: Vec<FileChange>) {
+    if let Err(e) = apply(&ws.root_path, relocation, changes) {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Moves the directory of the package first and then writes the planned changes to the
+/// files at their new locations. If writing fails, the files are restored and the
+/// directory is moved back, so nothing is changed. Returns the message of the error.
+fn apply(
+    root_path: &Path,
+    relocation: &Relocation,
+    changes: Vec<FileChange>,
+) -> Result<(), String> {
+    let display = |path: &Path| strip_root(root_path, path).display().to_string();
+
+    // The outermost directory that is created for the new location, which is
+    // removed again if something fails.
+    let created = relocation
+        .new_path
+        .ancestors()
+        .skip(1)
+        .take_while(|p| !p.as_os_str().is_empty() && !p.exists())
+        .last()
+        .map(Path::to_path_buf);
+    let remove_created = || {
+        if let Some(dir) = &created {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    };
+
+    if relocation.is_move() {
+        let result = match relocation.new_path.parent() {
+            Some(parent) if !parent.is_dir() => fs::mkdir(parent, true),
+            _ => Ok(()),
+        }
+        .and_then(|_| fs::move_path(&relocation.old_path, &relocation.new_path));
+
+        if let Err(e) = result {
+            remove_created();
+            return Err(format!(
+                "Moving {} failed with error: {}",
+                display(&relocation.old_path),
+                e
+            ));
+        }
+    }
+
+    let files: Vec<(PathBuf, String, String)> = changes
+        .into_iter()
+        .map(|c| (relocation.moved(&c.path), c.content, c.original))
+        .collect();
+    if let Err(e) = fs::replace_files(&files) {
+        let mut message = format!("Writing the changes failed with error: {}", e);
+        if relocation.is_move() {
+            match fs::move_path(&relocation.new_path, &relocation.old_path) {
+                Ok(_) => remove_created(),
+                Err(e) => {
+                    message += &format!(
+                        "\nMoving {} back failed with error: {}",
+                        display(&relocation.new_path),
+                        e
+                    )
+                }
+            }
+        }
+        return Err(message);
+    }
+
+    Ok(())
+}
+
+/// Asks the user whether the changes should be applied.
+pub fn confirm_changes() -> bool {
+    if !input::is_interactive() {
+        println!("Standard input is not a terminal, so no questions can be asked.");
+        println!("Use --yes to apply the changes without confirmation.");
+        std::process::exit(1);
+    }
+
+    input::prompt_yes_no("Apply these changes?", input::DefaultBool::No).unwrap_or(false)
+}
+
+/// Returns `path` relative to the root of the workspace, for displaying it.
+pub fn display_path(ws: &Workspace, path: &Path) -> PathBuf {
+    strip_root(&ws.root_path, path)
+}
+
+/// Returns `path` relative to `root_path`, or unchanged if it is outside of it.
+fn strip_root(root_path: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root_path)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Updates the members, the `[workspace.dependencies]` and the \[patch\] sections of the
+/// root Cargo.toml. `directory` is the directory of the package, relative to `root_path`.
+/// Returns true if the key of a workspace dependency was renamed.
+fn update_root_manifest(
+    manifest: &mut ManifestDocument,
+    root_path: &Path,
+    directory: &Path,
+    relocation: &Relocation,
+) -> bool {
+    if relocation.is_move() {
+        let new_directory = fs::relative_path(root_path, &relocation.new_path);
+        let new_member = new_directory.to_string_lossy();
+        let members = manifest.workspace_members();
+
+        match members
+            .iter()
+            .find(|m| fs::normalize_path(Path::new(m)) == directory)
+        {
+            Some(m) => {
+                manifest.replace_workspace_member(m, &new_member);
+            }
+            // Only add the new directory if no glob pattern matches it.
+            None if !workspace::matches_member_pattern(&members, &new_directory) => {
+                manifest.add_workspace_member(&new_member);
+            }
+            None => {}
+        }
+    }
+
+    for patches in manifest.patch_tables_mut() {
+        update_dependencies(patches, root_path, root_path, relocation, false);
+    }
+
+    match manifest.workspace_dependencies_mut() {
+        Some(deps) => update_dependencies(deps, root_path, root_path, relocation, false),
+        None => false,
+    }
+}
+
+/// Updates a dependency table of the Cargo.toml in `manifest_dir`, which is located
+/// in `new_manifest_dir` after the relocation. Paths are changed to point to the new
+/// locations, and dependencies on the package get its new name. Dependencies inherited
+/// from the workspace are renamed as well if `workspace_key_renamed`.
+/// Returns true if a key was renamed, so the sources have to be changed too.
+fn update_dependencies(
+    deps: &mut dyn TableLike,
+    manifest_dir: &Path,
+    new_manifest_dir: &Path,
+    relocation: &Relocation,
+    workspace_key_renamed: bool,
+) -> bool {
+    let mut renamed_keys = Vec::new();
+
+    for (key, dep) in deps.iter_mut() {
+        let dep = match dep.as_table_like_mut() {
+            Some(d) => d,
+            None => continue,
+        };
+
+        let refers = match dep.get("path").and_then(|p| p.as_str()).map(PathBuf::from) {
+            Some(path) => {
+                let target = fs::normalize_path(&manifest_dir.join(&path));
+                let new_path = fs::relative_path(new_manifest_dir, &relocation.moved(&target));
+                if fs::normalize_path(&path) != new_path {
+                    if let Some(p) = dep.get_mut("path").and_then(|p| p.as_value_mut()) {
+                        cargo_toml::set_string(p, &new_path.to_string_lossy());
+                    }
+                }
+                target == relocation.old_path
+            }
+            None => {
+                workspace_key_renamed
+                    && dep.get("workspace").and_then(|w| w.as_bool()) == Some(true)
+            }
+        };
+
+        if !refers || relocation.old_name == relocation.new_name {
+            continue;
+        }
+
+        match dep.get_mut("package").and_then(|p| p.as_value_mut()) {
+            Some(p) => cargo_toml::set_string(p, relocation.new_name),
+            None if key.get() == relocation.old_name => renamed_keys.push(key.get().to_owned()),
+            None => {}
+        }
+    }
+
+    let mut renamed = false;
+    for key in renamed_keys {
+        renamed |= cargo_toml::rename_key(deps, &key, relocation.new_name);
+    }
+
+    renamed
+}
+
+/// Replaces the references to the crate `old_ident` in all source files of the package
+/// in `package_path`. Returns the files that are changed.
+fn rename_in_sources(package_path: &Path, old_ident: &str, new_ident: &str) -> Vec<FileChange> {
+    let files = match sources::source_files(package_path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!(
+                "Searching {} failed with error: {}",
+                package_path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let mut changes = Vec::new();
+    for file in files {
+        let original = match fs::read_file(&file) {
+            Ok(s) => s,
+            Err(e) => {
+                println!("Reading {} failed with error: {}", file.display(), e);
+                std::process::exit(1);
+            }
+        };
+
+        let content = sources::replace_crate(&original, old_ident, new_ident);
+        if content != original {
+            changes.push(FileChange {
+                path: file,
+                original,
+                content,
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::context_setup::TestFiles;
+    use test_context::test_context;
+
+    #[test]
+    fn test_update_dependencies() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("/ws/app/Cargo.toml"),
+            "\
+[dependencies]
+old-core = { path = \"../crates/old-core\" } # the core
+util = { path = \"../util\" }
+serde = \"1.0\"
+
+[dev-dependencies]
+core = { path = \"../crates/old-core\", package = \"old-core\" }
+
+[build-dependencies]
+old-core = { workspace = true }
+",
+        )
+        .unwrap();
+
+        let relocation = Relocation {
+            old_name: "old-core",
+            new_name: "core",
+            old_path: PathBuf::from("/ws/crates/old-core"),
+            new_path: PathBuf::from("/ws/crates/core"),
+        };
+
+        let dir = Path::new("/ws/app");
+        let renamed: Vec<bool> = manifest
+            .dependency_tables_mut()
+            .into_iter()
+            .map(|(_, deps)| update_dependencies(deps, dir, dir, &relocation, true))
+            .collect();
+
+        assert_eq!(renamed, vec![true, false, true]);
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[dependencies]
+core = { path = \"../crates/core\" } # the core
+util = { path = \"../util\" }
+serde = \"1.0\"
+
+[dev-dependencies]
+core = { path = \"../crates/core\", package = \"core\" }
+
+[build-dependencies]
+core = { workspace = true }
+"
+        );
+    }
+
+    #[test]
+    fn test_moved_package_keeps_its_dependencies() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("/ws/old/Cargo.toml"),
+            "\
+[dependencies]
+util = { path = \"../util\" }
+inner = { path = \"inner\" }
+",
+        )
+        .unwrap();
+
+        let relocation = Relocation {
+            old_name: "old",
+            new_name: "old",
+            old_path: PathBuf::from("/ws/old"),
+            new_path: PathBuf::from("/ws/crates/old"),
+        };
+
+        let old_dir = Path::new("/ws/old");
+        for (_, deps) in manifest.dependency_tables_mut() {
+            assert!(!update_dependencies(
+                deps,
+                old_dir,
+                &relocation.moved(old_dir),
+                &relocation,
+                false
+            ));
+        }
+
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[dependencies]
+util = { path = \"../../util\" }
+inner = { path = \"inner\" }
+"
+        );
+    }
+
+    #[test]
+    fn test_update_root_manifest() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("/ws/Cargo.toml"),
+            "\
+[workspace]
+members = [\"app\", \"old\"]
+
+[workspace.dependencies]
+old = { path = \"old\", version = \"0.1\" }
+
+[patch.crates-io]
+old = { path = \"./old\" }
+",
+        )
+        .unwrap();
+
+        let relocation = Relocation {
+            old_name: "old",
+            new_name: "old",
+            old_path: PathBuf::from("/ws/old"),
+            new_path: PathBuf::from("/ws/crates/old"),
+        };
+
+        let renamed = update_root_manifest(
+            &mut manifest,
+            Path::new("/ws"),
+            Path::new("old"),
+            &relocation,
+        );
+
+        assert!(!renamed);
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[workspace]
+members = [\"app\", \"crates/old\"]
+
+[workspace.dependencies]
+old = { path = \"crates/old\", version = \"0.1\" }
+
+[patch.crates-io]
+old = { path = \"crates/old\" }
+"
+        );
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_apply(ctx: &mut TestFiles) {
+        let root = PathBuf::from(&ctx.name);
+        std::fs::create_dir_all(root.join("old")).unwrap();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(root.join("old/Cargo.toml"), "old").unwrap();
+        std::fs::write(root.join("app/Cargo.toml"), "app").unwrap();
+
+        let relocation = Relocation {
+            old_name: "old",
+            new_name: "new",
+            old_path: root.join("old"),
+            new_path: root.join("crates/new"),
+        };
+        let change = |path: &str, content: &str| FileChange {
+            path: root.join(path),
+            original: std::fs::read_to_string(root.join(path)).unwrap_or_default(),
+            content: content.to_owned(),
+        };
+
+        // The directory src doesn't exist, so writing fails and everything is rolled back.
+        let changes = vec![
+            change("app/Cargo.toml", "new app"),
+            change("old/Cargo.toml", "new"),
+            change("old/src/lib.rs", "pub use new;"),
+        ];
+        assert!(apply(&root, &relocation, changes).is_err());
+        assert_eq!(read_file(&root.join("old/Cargo.toml")), "old");
+        assert_eq!(read_file(&root.join("app/Cargo.toml")), "app");
+        assert!(!root.join("crates").exists());
+
+        // Moving a directory that doesn't exist fails before anything is written.
+        let missing = Relocation {
+            old_name: "old",
+            new_name: "new",
+            old_path: root.join("missing"),
+            new_path: root.join("crates/new"),
+        };
+        let changes = vec![change("app/Cargo.toml", "new app")];
+        assert!(apply(&root, &missing, changes).is_err());
+        assert_eq!(read_file(&root.join("app/Cargo.toml")), "app");
+        assert!(!root.join("crates").exists());
+
+        let changes = vec![
+            change("app/Cargo.toml", "new app"),
+            change("old/Cargo.toml", "new"),
+        ];
+        assert!(apply(&root, &relocation, changes).is_ok());
+        assert_eq!(read_file(&root.join("crates/new/Cargo.toml")), "new");
+        assert_eq!(read_file(&root.join("app/Cargo.toml")), "new app");
+        assert!(!root.join("old").exists());
+    }
+
+    fn read_file(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+}
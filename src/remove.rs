@@ -0,0 +1,326 @@
+//! This module invokes the execution of the 'REMOVE' command.
+
+use std::path::{Path, PathBuf};
+
+use toml_edit::{Item, TableLike};
+
+use crate::config::Remove;
+use crate::fs::{self, ManifestDocument};
+use crate::input;
+use crate::package::Package;
+use crate::sources;
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &Remove) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let removed = match ws.package(&cfg.crate_name) {
+        Some(p) if p.directory == Path::new(".") => {
+            println!("{} is the root crate and cannot be removed", cfg.crate_name);
+            std::process::exit(1);
+        }
+        Some(p) => p,
+        None => {
+            println!("{} is not a member of the workspace", cfg.crate_name);
+            std::process::exit(1);
+        }
+    };
+    let removed_path = ws.package_path(removed);
+    let dependents = ws.dependents(&removed.name);
+
+    let users = find_users(&ws, &dependents, &removed.name);
+    if !users.is_empty() && !cfg.force {
+        println!(
+            "{} is still used by the following files (use --force to remove it anyway):",
+            cfg.crate_name
+        );
+        for file in users {
+            println!("    {}", file.display());
+        }
+        std::process::exit(1);
+    }
+
+    let removed_dirname = removed.directory.to_string_lossy().into_owned();
+    let delete = cfg.delete || confirm_deletion(&removed_dirname);
+
+    let mut root_manifest = fs::load_manifest_or_handle_error(&ws.root_path.to_string_lossy());
+    let member_entry = root_manifest
+        .workspace_members()
+        .into_iter()
+        .find(|m| fs::normalize_path(Path::new(m)) == removed.directory);
+
+    match member_entry {
+        Some(m) => {
+            root_manifest.remove_workspace_member(&m);
+        }
+        // The crate is matched by a glob pattern, so it has to be excluded explicitly.
+        None if !delete => {
+            root_manifest.add_to_workspace_list("exclude", &removed_dirname);
+        }
+        None => {}
+    }
+    let inherited = remove_workspace_dependencies(&mut root_manifest, &ws.root_path, &removed_path);
+
+    let mut manifests = vec![(PathBuf::from("."), root_manifest)];
+    for dependent in &dependents {
+        if dependent.directory != Path::new(".") {
+            manifests.push((
+                dependent.directory.clone(),
+                fs::load_manifest_or_handle_error(&ws.package_path(dependent).to_string_lossy()),
+            ));
+        }
+    }
+
+    println!(
+        "Removed {} from the members of the workspace",
+        cfg.crate_name
+    );
+
+    for (directory, manifest) in &mut manifests {
+        let keys = remove_path_dependencies(
+            manifest,
+            &ws.root_path.join(&*directory),
+            &removed_path,
+            &inherited,
+        );
+        let features: Vec<(String, String)> = keys
+            .iter()
+            .flat_map(|key| manifest.remove_feature_dependency(key))
+            .collect();
+        fs::save_manifest_or_handle_error(manifest);
+        println!("Updated {}", directory.join("Cargo.toml").display());
+        for (feature, entry) in features {
+            println!("    removed \"{}\" from the feature {}", entry, feature);
+        }
+    }
+
+    if delete {
+        match fs::rmdir(&removed_path, true) {
+            Ok(_) => println!("Deleted {}", removed_dirname),
+            Err(e) => {
+                println!("Deleting {} failed with error: {}", removed_dirname, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Removes all dependencies of a Cargo.toml which point to `removed_path`,
+/// including target-specific ones and the ones inheriting an entry named in
+/// `inherited` from `[workspace.dependencies]`. Returns the names of the removed dependencies.
+fn remove_path_dependencies(
+    manifest: &mut ManifestDocument,
+    manifest_dir: &Path,
+    removed_path: &Path,
+    inherited: &[String],
+) -> Vec<String> {
+    let mut removed = Vec::new();
+
+    for (_, deps) in manifest.dependency_tables_mut() {
+        let matching = remove_matching(deps, |key, dep| {
+            points_to(dep, manifest_dir, removed_path)
+                || (inherited.iter().any(|i| i == key)
+                    && dep.get("workspace").and_then(|w| w.as_bool()) == Some(true))
+        });
+
+        for key in matching {
+            if !removed.contains(&key) {
+                removed.push(key);
+            }
+        }
+    }
+
+    removed
+}
+
+/// Removes the entries of `[workspace.dependencies]` which point to `removed_path`.
+/// Returns their names, which members use to inherit them.
+fn remove_workspace_dependencies(
+    manifest: &mut ManifestDocument,
+    root_path: &Path,
+    removed_path: &Path,
+) -> Vec<String> {
+    match manifest.workspace_dependencies_mut() {
+        Some(deps) => remove_matching(deps, |_, dep| points_to(dep, root_path, removed_path)),
+        None => Vec::new(),
+    }
+}
+
+/// Removes the dependencies for which `matches` is true from a table and returns their keys.
+fn remove_matching(deps: &mut dyn TableLike, matches: impl Fn(&str, &Item) -> bool) -> Vec<String> {
+    let matching: Vec<String> = deps
+        .iter()
+        .filter(|(key, dep)| matches(key, dep))
+        .map(|(key, _)| key.to_owned())
+        .collect();
+
+    for key in &matching {
+        deps.remove(key);
+    }
+
+    matching
+}
+
+/// Whether a dependency has a path which points to `target_path` when
+/// resolved relative to `manifest_dir`.
+fn points_to(dep: &Item, manifest_dir: &Path, target_path: &Path) -> bool {
+    dep.get("path")
+        .and_then(|p| p.as_str())
+        .is_some_and(|p| fs::normalize_path(&manifest_dir.join(p)) == target_path)
+}
+
+/// Searches the source files of the dependents for usages of the package `name`.
+fn find_users(ws: &Workspace, dependents: &[&Package], name: &str) -> Vec<PathBuf> {
+    let mut users = Vec::new();
+
+    for dependent in dependents {
+        let keys: Vec<String> = dependent
+            .dependencies
+            .iter()
+            .filter(|d| d.package == name)
+            .map(|d| d.key.replace('-', "_"))
+            .collect();
+
+        let package_path = ws.package_path(dependent);
+        let files = match sources::source_files(&package_path) {
+            Ok(f) => f,
+            Err(e) => {
+                println!(
+                    "Searching {} failed with error: {}",
+                    package_path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        };
+
+        for file in files {
+            let source = match fs::read_file(&file) {
+                Ok(s) => s,
+                Err(e) => {
+                    println!("Reading {} failed with error: {}", file.display(), e);
+                    std::process::exit(1);
+                }
+            };
+
+            if keys.iter().any(|key| sources::uses_crate(&source, key)) {
+                users.push(
+                    file.strip_prefix(&ws.root_path)
+                        .map(Path::to_path_buf)
+                        .unwrap_or(file),
+                );
+            }
+        }
+    }
+
+    users
+}
+
+/// Asks the user whether a directory should be deleted until a valid answer is given.
+fn confirm_deletion(dirname: &str) -> bool {
+    loop {
+        if let Some(answer) = input::prompt_yes_no(
+            &format!("Delete directory {}?", dirname),
+            input::DefaultBool::None,
+        ) {
+            return answer;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_path_dependencies() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("bar/Cargo.toml"),
+            "\
+[dependencies]
+foo = { path = \"../foo\" }
+serde = \"1.0\"
+
+[dev-dependencies]
+renamed = { path = \"../foo\", package = \"foo\" }
+
+[target.'cfg(unix)'.build-dependencies]
+foo = { path = \"../foo/\" }
+other = { path = \"../other\" }
+",
+        )
+        .unwrap();
+
+        let removed =
+            remove_path_dependencies(&mut manifest, Path::new("./bar"), Path::new("foo"), &[]);
+
+        assert_eq!(removed, vec![String::from("foo"), String::from("renamed")]);
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[dependencies]
+serde = \"1.0\"
+
+[dev-dependencies]
+
+[target.'cfg(unix)'.build-dependencies]
+other = { path = \"../other\" }
+"
+        );
+    }
+
+    #[test]
+    fn test_remove_inherited_dependencies() {
+        let mut root = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[workspace]
+members = [\"crates/*\"]
+
+[workspace.dependencies]
+foo = { path = \"crates/foo\" }
+serde = \"1.0\"
+",
+        )
+        .unwrap();
+        let mut bar = ManifestDocument::parse(
+            PathBuf::from("crates/bar/Cargo.toml"),
+            "\
+[dependencies]
+foo = { workspace = true }
+serde = { workspace = true }
+
+[dev-dependencies.foo]
+workspace = true
+features = [\"test\"]
+",
+        )
+        .unwrap();
+
+        let inherited =
+            remove_workspace_dependencies(&mut root, Path::new("."), Path::new("crates/foo"));
+        assert_eq!(inherited, vec![String::from("foo")]);
+        assert_eq!(
+            root.to_string(),
+            "\
+[workspace]
+members = [\"crates/*\"]
+
+[workspace.dependencies]
+serde = \"1.0\"
+"
+        );
+
+        let removed = remove_path_dependencies(
+            &mut bar,
+            Path::new("./crates/bar"),
+            Path::new("crates/foo"),
+            &inherited,
+        );
+        assert_eq!(removed, vec![String::from("foo")]);
+        assert_eq!(
+            bar.to_string(),
+            "[dependencies]\nserde = { workspace = true }\n"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! This module invokes the execution of the 'RENAME' command.
+
+use std::path::Path;
+
+use crate::config::Rename;
+use crate::package;
+use crate::relocation::{self, Relocation};
+use crate::workspace::Workspace;
+
+pub fn run(cfg: &Rename) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+
+    let package = match ws.package(&cfg.old_name) {
+        Some(p) => p,
+        None => {
+            println!("{} is not a member of the workspace", cfg.old_name);
+            std::process::exit(1);
+        }
+    };
+
+    if !package::is_valid_name(&cfg.new_name) {
+        println!("{} is not a valid crate name", cfg.new_name);
+        std::process::exit(1);
+    }
+    if ws.package(&cfg.new_name).is_some() {
+        println!("{} is already a member of the workspace", cfg.new_name);
+        std::process::exit(1);
+    }
+    if cfg.move_dir && package.directory == Path::new(".") {
+        println!(
+            "{} is the root crate, so its directory cannot be moved",
+            cfg.old_name
+        );
+        std::process::exit(1);
+    }
+
+    let old_path = ws.package_path(package);
+    let new_path = match cfg.move_dir {
+        true => old_path.with_file_name(&cfg.new_name),
+        false => old_path.clone(),
+    };
+    if cfg.move_dir && new_path.exists() {
+        println!(
+            "{} already exists",
+            relocation::display_path(&ws, &new_path).display()
+        );
+        std::process::exit(1);
+    }
+
+    let relocation = Relocation {
+        old_name: &cfg.old_name,
+        new_name: &cfg.new_name,
+        old_path,
+        new_path,
+    };
+
+    let changes = relocation::plan(&ws, &relocation);
+    relocation::print_changes(&ws, &relocation, &changes);
+
+    if cfg.dry_run {
+        println!("This was a dry run, nothing has been changed");
+        return;
+    }
+
+    if !cfg.yes && !relocation::confirm_changes() {
+        println!("Nothing has been changed");
+        return;
+    }
+
+    relocation::apply_or_handle_error(&ws, &relocation, changes);
+    println!("Renamed {} to {}", cfg.old_name, cfg.new_name);
+}
@@ -0,0 +1,267 @@
+//! Finds the source files of a crate and the places where they refer to other crates.
+
+use std::path::{Path, PathBuf};
+
+use crate::fs::{self, IOError};
+
+/// Directories of a crate that can contain source code.
+const SOURCE_DIRS: [&str; 4] = ["src", "tests", "benches", "examples"];
+
+/// Returns all rust source files of the crate in `package_path`, including its build script.
+pub fn source_files(package_path: &Path) -> Result<Vec<PathBuf>, IOError> {
+    let mut files = Vec::new();
+
+    for source_dir in SOURCE_DIRS {
+        let path = package_path.join(source_dir);
+        if path.is_dir() {
+            files.append(&mut fs::find_files(&path, "rs")?);
+        }
+    }
+
+    let build_script = package_path.join("build.rs");
+    if build_script.is_file() {
+        files.push(build_script);
+    }
+
+    Ok(files)
+}
+
+/// Finds the positions where some source code refers to the crate `ident`, either
+/// with a path like `ident::...` or `::ident::...`, or with `use ident` / `extern crate ident`.
+/// Comments and literals are skipped, and so are paths like `crate::ident`, `self::ident`
+/// or `x.ident::<T>()`, which refer to something else. If the file declares a module
+/// `ident`, only absolute paths and `extern crate` refer to the crate.
+fn crate_references(source: &str, ident: &str) -> Vec<usize> {
+    let code = code_mask(source);
+    let candidates: Vec<usize> = source
+        .match_indices(ident)
+        .map(|(pos, _)| pos)
+        .filter(|&pos| {
+            code[pos]
+                && !source[..pos].ends_with(is_ident_char)
+                && !source[pos + ident.len()..].starts_with(is_ident_char)
+        })
+        .collect();
+    let shadowed = candidates
+        .iter()
+        .any(|&pos| ends_with_keyword(source[..pos].trim_end(), "mod"));
+
+    candidates
+        .into_iter()
+        .filter(|&pos| {
+            let before = source[..pos].trim_end();
+            let after = source[pos + ident.len()..].trim_start();
+
+            // `::ident` is an absolute path, but `a::ident` is an item of `a`.
+            if let Some(parent) = before.strip_suffix("::") {
+                let parent = parent.trim_end();
+                return ends_with_keyword(parent, "use")
+                    || !(parent.ends_with(is_ident_char) || parent.ends_with('>'));
+            }
+            if before.ends_with('.') && !before.ends_with("..") {
+                return false;
+            }
+            if let Some(extern_crate) = before.strip_suffix("crate") {
+                if ends_with_keyword(before, "crate")
+                    && ends_with_keyword(extern_crate.trim_end(), "extern")
+                {
+                    return true;
+                }
+            }
+
+            !shadowed && (after.starts_with("::") || ends_with_keyword(before, "use"))
+        })
+        .collect()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `s` ends with the keyword `keyword`, and not just with an identifier ending like it.
+fn ends_with_keyword(s: &str, keyword: &str) -> bool {
+    s.strip_suffix(keyword)
+        .is_some_and(|rest| !rest.ends_with(is_ident_char))
+}
+
+/// Marks which bytes of some source code are code, as opposed to
+/// comments, string literals and character literals.
+fn code_mask(source: &str) -> Vec<bool> {
+    let bytes = source.as_bytes();
+    let mut mask = vec![true; bytes.len()];
+    let find = |from: usize, pattern: &str| {
+        source
+            .get(from..)
+            .and_then(|rest| rest.find(pattern))
+            .map_or(bytes.len(), |i| from + i + pattern.len())
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let follows_ident = source[..i].ends_with(is_ident_char);
+        let end = match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => find(i, "\n"),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => block_comment_end(source, i),
+            b'"' => string_end(bytes, i + 1),
+            b'r' | b'b' if !follows_ident => match raw_string_start(source, i) {
+                Some((hashes, content)) => find(content, &format!("\"{}", "#".repeat(hashes))),
+                None => i + 1,
+            },
+            b'\'' => match source[i + 1..].chars().next() {
+                Some('\\') => find(i + 3, "'"),
+                Some(c) if source[i + 1 + c.len_utf8()..].starts_with('\'') => i + 2 + c.len_utf8(),
+                // A lifetime or a label.
+                _ => i + 1,
+            },
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        if !matches!(bytes[i], b'r' | b'b' | b'\'') || end > i + 1 {
+            mask[i..end].fill(false);
+        }
+        i = end;
+    }
+
+    mask
+}
+
+/// Returns the end of a block comment starting at `start`, which may contain nested ones.
+fn block_comment_end(source: &str, start: usize) -> usize {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+/// Returns the end of a string literal whose content starts at `content`.
+fn string_end(bytes: &[u8], content: usize) -> usize {
+    let mut i = content;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+/// Checks whether a raw string literal like `r#"..."#` or `br"..."` starts at `start`.
+/// Returns the number of `#` and the position its content starts at.
+fn raw_string_start(source: &str, start: usize) -> Option<(usize, usize)> {
+    let rest = source[start..]
+        .strip_prefix('b')
+        .unwrap_or(&source[start..]);
+    let rest = rest.strip_prefix('r')?;
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    rest[hashes..].starts_with('"').then(|| {
+        let content = source.len() - rest.len() + hashes + 1;
+        (hashes, content)
+    })
+}
+
+/// Checks whether some source code refers to the crate `ident`.
+pub fn uses_crate(source: &str, ident: &str) -> bool {
+    !crate_references(source, ident).is_empty()
+}
+
+/// Replaces every reference to the crate `old` in some source code with `new`.
+pub fn replace_crate(source: &str, old: &str, new: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut last = 0;
+
+    for pos in crate_references(source, old) {
+        result.push_str(&source[last..pos]);
+        result.push_str(new);
+        last = pos + old.len();
+    }
+    result.push_str(&source[last..]);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_crate() {
+        assert!(uses_crate("use foo_bar::run;", "foo_bar"));
+        assert!(uses_crate("fn main() { foo_bar::run(); }", "foo_bar"));
+        assert!(uses_crate("extern crate foo_bar;", "foo_bar"));
+        assert!(!uses_crate("use my_foo_bar::run;", "foo_bar"));
+        assert!(!uses_crate("let foo_bar = 1;", "foo_bar"));
+        assert!(uses_crate("let x = ::foo_bar::run();", "foo_bar"));
+        assert!(uses_crate("pub use ::foo_bar;", "foo_bar"));
+    }
+
+    #[test]
+    fn test_uses_crate_ignores_other_paths() {
+        assert!(!uses_crate("use crate::foo::x;", "foo"));
+        assert!(!uses_crate("let y = super::foo::x();", "foo"));
+        assert!(!uses_crate("use self::foo::{a, b};", "foo"));
+        assert!(!uses_crate("let y = Vec::<u8>::foo::x;", "foo"));
+        assert!(!uses_crate("let y = x.foo::<u8>();", "foo"));
+        assert!(!uses_crate("mod foo;\nfn f() { foo::f(); }", "foo"));
+        assert!(uses_crate("mod foo;\nfn f() { ::foo::f(); }", "foo"));
+        assert!(!uses_crate("reuse foo;\nsome_use foo;", "foo"));
+        assert!(uses_crate("pub(crate) use foo;", "foo"));
+    }
+
+    #[test]
+    fn test_uses_crate_ignores_comments_and_literals() {
+        assert!(!uses_crate("// foo::run()\nfn f() {}", "foo"));
+        assert!(!uses_crate("/* use foo; /* nested */ foo::x */", "foo"));
+        assert!(!uses_crate("let s = \"foo::run() \\\" use foo\";", "foo"));
+        assert!(!uses_crate("let s = r#\"foo::run() \"# \"#;", "foo"));
+        assert!(!uses_crate("let s = b\"foo::x\";", "foo"));
+        assert!(uses_crate("let c = '\"'; foo::run(); // \"", "foo"));
+        assert!(uses_crate("fn f<'a>(x: &'a str) { foo::run(x) }", "foo"));
+        assert!(uses_crate("let c = '\\''; foo::run();", "foo"));
+        assert!(uses_crate("/* */ let r = 1; foo::run(r);", "foo"));
+        assert!(uses_crate("foo::run('\\", "foo"));
+    }
+
+    #[test]
+    fn test_replace_crate() {
+        assert_eq!(
+            replace_crate(
+                "use foo::{a, b};\nlet foo = foo::run(my_foo::X);\n",
+                "foo",
+                "bar"
+            ),
+            "use bar::{a, b};\nlet foo = bar::run(my_foo::X);\n"
+        );
+        assert_eq!(
+            replace_crate(
+                "// foo::run\nfoo::run(\"foo::run\");\ncrate::foo::x();\n",
+                "foo",
+                "bar"
+            ),
+            "// foo::run\nbar::run(\"foo::run\");\ncrate::foo::x();\n"
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! Templates for the files of new crates. There are built-in templates for the usual kinds
+//! of crates, and user templates are loaded from the subdirectories of a template directory.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use toml_edit::Document;
+
+use crate::config::CrateKind;
+use crate::fs;
+use crate::package::TargetKind;
+
+/// Name of the file of a user template that is merged into the generated Cargo.toml
+/// instead of being copied.
+const MANIFEST_FILE: &str = "Cargo.toml";
+
+/// Names of the built-in templates.
+pub const BUILT_IN: [&str; 7] = [
+    "lib",
+    "bin",
+    "bin-lib",
+    "proc-macro",
+    "cdylib",
+    "test",
+    "bench",
+];
+
+const LIB_RS: &str = "\
+pub fn run() {
+    println!(\"Hello, world!\");
+}
+";
+
+const MAIN_RS: &str = "\
+fn main() {
+    println!(\"Hello, world!\");
+}
+";
+
+const BIN_LIB_MAIN_RS: &str = "\
+fn main() {
+    {{crate_ident}}::run();
+}
+";
+
+const PROC_MACRO_MANIFEST: &str = "\
+[lib]
+proc-macro = true
+
+[dependencies]
+proc-macro2 = \"1.0\"
+quote = \"1.0\"
+syn = \"2.0\"
+";
+
+const PROC_MACRO_LIB_RS: &str = "\
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implements `{{crate_name}}` for a type.
+#[proc_macro_derive(Hello)]
+pub fn derive_hello(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+
+    let expanded = quote! {
+        impl #name {
+            pub fn hello() {
+                println!(\"Hello from {}!\", stringify!(#name));
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+";
+
+const CDYLIB_MANIFEST: &str = "\
+[lib]
+crate-type = [\"cdylib\"]
+";
+
+const CDYLIB_LIB_RS: &str = "\
+/// Adds two numbers. Can be called from C as `int32_t add(int32_t, int32_t)`.
+#[no_mangle]
+pub extern \"C\" fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+";
+
+const TEST_MANIFEST: &str = "\
+[package]
+publish = false
+";
+
+const TEST_LIB_RS: &str = "\
+//! Integration tests of {{project_name}}.
+";
+
+const TEST_TESTS_RS: &str = "\
+#[test]
+fn it_works() {
+    assert_eq!(2 + 2, 4);
+}
+";
+
+const BENCH_MANIFEST: &str = "\
+[package]
+publish = false
+
+[[bench]]
+name = \"{{crate_name}}\"
+harness = false
+";
+
+const BENCH_BENCHES_RS: &str = "\
+use std::hint::black_box;
+use std::time::Instant;
+
+fn main() {
+    let start = Instant::now();
+    for i in 0..1_000_000u64 {
+        black_box(i * i);
+    }
+    println!(\"took {:?}\", start.elapsed());
+}
+";
+
+/// Returns the content of the root source file of a target
+/// that is declared in addition to the files of a template.
+pub fn target_source(kind: TargetKind) -> &'static str {
+    match kind {
+        TargetKind::Lib | TargetKind::ProcMacro => LIB_RS,
+        TargetKind::Bin | TargetKind::Example => MAIN_RS,
+        TargetKind::Test => TEST_TESTS_RS,
+        TargetKind::Bench => BENCH_BENCHES_RS,
+    }
+}
+
+/// A template for the files of a new crate.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Template {
+    pub name: String,
+
+    /// Paths relative to the directory of the crate, together with the content of the files.
+    files: Vec<(String, String)>,
+
+    /// Sections that are merged into the generated Cargo.toml, like \[lib\].
+    manifest: Option<String>,
+}
+
+/// The values that replace the placeholders like `{{crate_name}}`
+/// in the paths and the content of the files of a template.
+pub struct Placeholders<'a> {
+    pub crate_name: &'a str,
+    pub project_name: &'a str,
+    pub authors: &'a [String],
+}
+
+impl Placeholders<'_> {
+    /// Replaces all placeholders in `text`.
+    fn apply(&self, text: &str) -> String {
+        text.replace("{{crate_name}}", self.crate_name)
+            .replace("{{crate_ident}}", &self.crate_name.replace('-', "_"))
+            .replace("{{project_name}}", self.project_name)
+            .replace("{{author}}", &self.authors.join(", "))
+            .replace("{{year}}", &current_year().to_string())
+    }
+}
+
+impl Template {
+    /// Returns the built-in template `name`.
+    pub fn built_in(name: &str) -> Option<Template> {
+        let (files, manifest): (Vec<(&str, &str)>, Option<&str>) = match name {
+            "lib" => (vec![("src/lib.rs", LIB_RS)], None),
+            "bin" => (vec![("src/main.rs", MAIN_RS)], None),
+            "bin-lib" => (
+                vec![("src/lib.rs", LIB_RS), ("src/main.rs", BIN_LIB_MAIN_RS)],
+                None,
+            ),
+            "proc-macro" => (
+                vec![("src/lib.rs", PROC_MACRO_LIB_RS)],
+                Some(PROC_MACRO_MANIFEST),
+            ),
+            "cdylib" => (vec![("src/lib.rs", CDYLIB_LIB_RS)], Some(CDYLIB_MANIFEST)),
+            "test" => (
+                vec![
+                    ("src/lib.rs", TEST_LIB_RS),
+                    ("tests/{{crate_ident}}.rs", TEST_TESTS_RS),
+                ],
+                Some(TEST_MANIFEST),
+            ),
+            "bench" => (
+                vec![
+                    ("src/lib.rs", LIB_RS),
+                    ("benches/{{crate_name}}.rs", BENCH_BENCHES_RS),
+                ],
+                Some(BENCH_MANIFEST),
+            ),
+            _ => return None,
+        };
+
+        Some(Template {
+            name: name.to_owned(),
+            files: files
+                .into_iter()
+                .map(|(p, c)| (p.to_owned(), c.to_owned()))
+                .collect(),
+            manifest: manifest.map(str::to_owned),
+        })
+    }
+
+    /// Loads the template `name`. A subdirectory `name` of `template_dir` is preferred
+    /// over the built-in template of the same name.
+    pub fn load(name: &str, template_dir: Option<&Path>) -> Result<Template, String> {
+        if let Some(dir) = template_dir.map(|d| d.join(name)).filter(|d| d.is_dir()) {
+            return Template::from_directory(name, &dir);
+        }
+
+        Template::built_in(name).ok_or_else(|| {
+            format!(
+                "There is no template called {}. The built-in templates are {}{}",
+                name,
+                BUILT_IN.join(", "),
+                match template_dir {
+                    Some(d) => format!(", and others can be put into {}", d.display()),
+                    None => String::new(),
+                }
+            )
+        })
+    }
+
+    /// Loads a user template. All files in `dir` are copied into new crates,
+    /// except for its Cargo.toml, which is merged into the generated one.
+    fn from_directory(name: &str, dir: &Path) -> Result<Template, String> {
+        let error = |e: fs::IOError| format!("Loading the template {} failed: {}", name, e);
+
+        let mut template = Template {
+            name: name.to_owned(),
+            files: Vec::new(),
+            manifest: None,
+        };
+        for path in fs::list_files(dir).map_err(error)? {
+            let content = fs::read_file(&path).map_err(error)?;
+            let relative = match path.strip_prefix(dir) {
+                Ok(r) => r.to_string_lossy().replace('\\', "/"),
+                Err(_) => continue,
+            };
+
+            if relative == MANIFEST_FILE {
+                if let Err(e) = content.parse::<Document>() {
+                    return Err(format!(
+                        "The Cargo.toml of the template {} is invalid: {}",
+                        name, e
+                    ));
+                }
+                template.manifest = Some(content);
+            } else {
+                template.files.push((relative, content));
+            }
+        }
+
+        if !template.has_lib() && !template.has_main() {
+            return Err(format!(
+                "The template {} has neither a src/lib.rs nor a src/main.rs",
+                name
+            ));
+        }
+        Ok(template)
+    }
+
+    /// Whether crates created from the template have a library target.
+    pub fn has_lib(&self) -> bool {
+        self.files.iter().any(|(p, _)| p == "src/lib.rs")
+    }
+
+    /// Whether crates created from the template have a binary target.
+    pub fn has_main(&self) -> bool {
+        self.files.iter().any(|(p, _)| p == "src/main.rs")
+    }
+
+    /// Returns the kind of the crates created from the template. Templates with a library
+    /// target create proc-macro crates if their Cargo.toml sets `[lib] proc-macro = true`.
+    pub fn crate_kind(&self) -> CrateKind {
+        if !self.has_lib() {
+            return CrateKind::Bin;
+        }
+
+        let proc_macro = self
+            .manifest
+            .as_ref()
+            .and_then(|m| m.parse::<Document>().ok())
+            .and_then(|m| m.get("lib")?.get("proc-macro")?.as_bool());
+        match proc_macro {
+            Some(true) => CrateKind::ProcMacro,
+            _ => CrateKind::Lib,
+        }
+    }
+
+    /// Returns the sections that are merged into the Cargo.toml of a crate, if there are any.
+    pub fn manifest(&self, placeholders: &Placeholders) -> Result<Option<Document>, String> {
+        match &self.manifest {
+            Some(m) => placeholders.apply(m).parse::<Document>().map(Some).map_err(|e| {
+                format!(
+                    "The Cargo.toml of the template {} is invalid after filling in the placeholders: {}",
+                    self.name, e
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the files of a crate created from the template, relative to its directory.
+    pub fn files(&self, placeholders: &Placeholders) -> Vec<(String, String)> {
+        self.files
+            .iter()
+            .map(|(p, c)| (placeholders.apply(p), placeholders.apply(c)))
+            .collect()
+    }
+}
+
+/// Returns the current year, which is used for copyright notices.
+fn current_year() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    year_of_day((seconds / 86400) as i64)
+}
+
+/// Computes the year of a day, counted from 1970-01-01, in the Gregorian calendar.
+fn year_of_day(days: i64) -> i64 {
+    // Counts from 0000-03-01, so that leap days are at the end of the years.
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+
+    era * 400 + year_of_era + if month >= 10 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::context_setup::TestFiles;
+    use test_context::test_context;
+
+    #[test]
+    fn test_built_in() {
+        for name in BUILT_IN {
+            let template = Template::built_in(name).unwrap();
+            assert!(template.has_lib() || template.has_main(), "{}", name);
+        }
+        assert!(Template::built_in("bin-lib").unwrap().has_lib());
+        assert!(!Template::built_in("bin").unwrap().has_lib());
+        for kind in [CrateKind::Lib, CrateKind::Bin, CrateKind::ProcMacro] {
+            assert_eq!(Template::built_in(kind.name()).unwrap().crate_kind(), kind);
+        }
+        assert_eq!(
+            Template::built_in("bin-lib").unwrap().crate_kind(),
+            CrateKind::Lib
+        );
+        assert!(Template::built_in("unknown").is_none());
+
+        let placeholders = Placeholders {
+            crate_name: "my-bench",
+            project_name: "demo",
+            authors: &[],
+        };
+        let template = Template::built_in("bench").unwrap();
+        let files = template.files(&placeholders);
+        assert_eq!(files[1].0, "benches/my-bench.rs");
+        let manifest = template.manifest(&placeholders).unwrap().unwrap();
+        assert_eq!(manifest["bench"][0]["name"].as_str(), Some("my-bench"));
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_user_template(ctx: &mut TestFiles) {
+        let dir = Path::new(&ctx.name);
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            fs::mkdir(path.parent().unwrap(), true).unwrap();
+            fs::write_file(&path, &String::from(content), true).unwrap();
+        };
+        write(
+            "service/src/main.rs",
+            "// {{crate_name}} of {{project_name}}\n",
+        );
+        write(
+            "service/README.md",
+            "# {{crate_name}}\n\n(c) {{year}} {{author}}\n",
+        );
+        write("service/Cargo.toml", "[dependencies]\nlog = \"0.4\"\n");
+        write("lib/src/lib.rs", "// custom\n");
+        write("broken/README.md", "");
+
+        let template = Template::load("service", Some(dir)).unwrap();
+        assert!(template.has_main() && !template.has_lib());
+
+        let placeholders = Placeholders {
+            crate_name: "api",
+            project_name: "demo",
+            authors: &[String::from("Jane"), String::from("Joe")],
+        };
+        let files = template.files(&placeholders);
+        assert_eq!(
+            files,
+            vec![
+                (
+                    String::from("README.md"),
+                    format!("# api\n\n(c) {} Jane, Joe\n", current_year())
+                ),
+                (
+                    String::from("src/main.rs"),
+                    String::from("// api of demo\n")
+                ),
+            ]
+        );
+        let manifest = template.manifest(&placeholders).unwrap().unwrap();
+        assert_eq!(manifest["dependencies"]["log"].as_str(), Some("0.4"));
+
+        // User templates take precedence over the built-in ones.
+        let template = Template::load("lib", Some(dir)).unwrap();
+        assert_eq!(template.files(&placeholders)[0].1, "// custom\n");
+
+        assert!(Template::load("broken", Some(dir)).is_err());
+        assert!(Template::load("unknown", Some(dir)).is_err());
+        assert!(Template::load("cdylib", Some(dir)).is_ok());
+    }
+
+    #[test]
+    fn test_year_of_day() {
+        assert_eq!(year_of_day(0), 1970); // 1970-01-01
+        assert_eq!(year_of_day(364), 1970);
+        assert_eq!(year_of_day(365), 1971);
+        assert_eq!(year_of_day(10956), 1999); // 1999-12-31
+        assert_eq!(year_of_day(10957), 2000); // 2000-01-01
+        assert_eq!(year_of_day(11016), 2000); // 2000-02-29
+        assert_eq!(year_of_day(11322), 2000); // 2000-12-31
+        assert_eq!(year_of_day(11323), 2001);
+        assert_eq!(year_of_day(19782), 2024); // 2024-02-29
+        assert_eq!(year_of_day(20088), 2024); // 2024-12-31
+        assert_eq!(year_of_day(20089), 2025); // 2025-01-01
+        assert_eq!(year_of_day(47541), 2100); // 2100-03-01, not a leap year
+        assert_eq!(year_of_day(-1), 1969); // 1969-12-31
+        assert_eq!(year_of_day(-365), 1969); // 1969-01-01
+    }
+}
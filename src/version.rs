@@ -0,0 +1,350 @@
+//! This module invokes the execution of the 'VERSION' command.
+
+use std::path::{Path, PathBuf};
+
+use toml_edit::TableLike;
+
+use crate::config::{BumpLevel, Version, VersionBump, VersionMode};
+use crate::fs::{self, ManifestDocument};
+use crate::package::Package;
+use crate::workspace::Workspace;
+
+/// A Cargo.toml that might get changed, together with its original content.
+struct Manifest {
+    /// Directory of the Cargo.toml, relative to the root of the workspace.
+    directory: PathBuf,
+    content: ManifestDocument,
+    original: String,
+}
+
+pub fn run(cfg: &Version) {
+    let ws = Workspace::load_or_handle_error(Path::new("."));
+    let mode = cfg
+        .mode
+        .or(ws.settings.version_mode)
+        .unwrap_or(VersionMode::Independent);
+
+    if mode == VersionMode::Lockstep && !cfg.crates.is_empty() {
+        println!("All crates share one version in lockstep mode, so no crates can be selected");
+        std::process::exit(1);
+    }
+
+    let mut manifests = load_manifests(&ws);
+    let inherits = |package: &Package, manifests: &[Manifest]| {
+        manifests
+            .iter()
+            .find(|m| m.directory == package.directory)
+            .is_some_and(|m| m.content.inherits_package_value("version"))
+    };
+
+    let mut selected: Vec<&Package> = if cfg.crates.is_empty() {
+        ws.packages.iter().collect()
+    } else {
+        cfg.crates
+            .iter()
+            .map(|name| match ws.package(name) {
+                Some(p) => p,
+                None => {
+                    println!("{} is not a member of the workspace", name);
+                    std::process::exit(1);
+                }
+            })
+            .collect()
+    };
+
+    // Packages which inherit their version from the workspace can only change together.
+    if selected.iter().any(|p| inherits(p, &manifests)) {
+        for package in &ws.packages {
+            if inherits(package, &manifests) && !selected.iter().any(|p| p.name == package.name) {
+                selected.push(package);
+            }
+        }
+    }
+
+    let changes = new_versions(&selected, &cfg.bump, mode);
+
+    for (package, new_version) in &changes {
+        let new_version = new_version.to_string();
+        if inherits(package, &manifests) {
+            manifests[0]
+                .content
+                .set_workspace_package_value("version", &new_version);
+        } else if let Some(m) = manifests
+            .iter_mut()
+            .find(|m| m.directory == package.directory)
+        {
+            m.content.set_package_value("version", &new_version);
+        }
+    }
+
+    let versions: Vec<(PathBuf, String)> = changes
+        .iter()
+        .map(|(p, v)| (ws.package_path(p), v.to_string()))
+        .collect();
+    for manifest in &mut manifests {
+        let manifest_dir = ws.root_path.join(&manifest.directory);
+        update_version_requirements(&mut manifest.content, &manifest_dir, &versions);
+    }
+
+    for (package, new_version) in &changes {
+        println!(
+            "{} {} -> {}",
+            package.name,
+            package.version.as_deref().unwrap_or_default(),
+            new_version
+        );
+    }
+
+    if cfg.dry_run {
+        println!("This was a dry run, nothing has been changed");
+        return;
+    }
+
+    for manifest in manifests {
+        if manifest.content.to_string() != manifest.original {
+            fs::save_manifest_or_handle_error(&manifest.content);
+            println!(
+                "Updated {}",
+                manifest.directory.join("Cargo.toml").display()
+            );
+        }
+    }
+}
+
+/// Loads the Cargo.toml files of all packages. The root Cargo.toml is always the first one,
+/// even if there is no root package.
+fn load_manifests(ws: &Workspace) -> Vec<Manifest> {
+    let mut directories = vec![PathBuf::from(".")];
+    directories.extend(ws.members().map(|p| p.directory.clone()));
+
+    directories
+        .into_iter()
+        .map(|directory| {
+            let content =
+                fs::load_manifest_or_handle_error(&ws.root_path.join(&directory).to_string_lossy());
+            Manifest {
+                directory,
+                original: content.to_string(),
+                content,
+            }
+        })
+        .collect()
+}
+
+/// Computes the new version of every package in `selected`.
+fn new_versions<'a>(
+    selected: &[&'a Package],
+    bump: &VersionBump,
+    mode: VersionMode,
+) -> Vec<(&'a Package, semver::Version)> {
+    let current: Vec<semver::Version> = selected
+        .iter()
+        .map(
+            |p| match p.version.as_deref().map(str::parse::<semver::Version>) {
+                Some(Ok(v)) => v,
+                Some(Err(e)) => {
+                    println!("The version of {} is invalid: {}", p.name, e);
+                    std::process::exit(1);
+                }
+                None => {
+                    println!("{} has no version", p.name);
+                    std::process::exit(1);
+                }
+            },
+        )
+        .collect();
+
+    let new: Vec<semver::Version> = match (bump, mode) {
+        (VersionBump::Exact(v), _) => vec![v.clone(); current.len()],
+        (VersionBump::Level(level), VersionMode::Lockstep) => match current.iter().max() {
+            Some(highest) => vec![bumped(highest, *level); current.len()],
+            None => Vec::new(),
+        },
+        (VersionBump::Level(level), VersionMode::Independent) => {
+            current.iter().map(|v| bumped(v, *level)).collect()
+        }
+    };
+
+    selected.iter().copied().zip(new).collect()
+}
+
+/// Returns the version after `version`, with the pre-release and build metadata removed.
+/// Bumping a pre-release on the patch level only removes the pre-release,
+/// because `1.0.0-alpha` comes before `1.0.0`.
+fn bumped(version: &semver::Version, level: BumpLevel) -> semver::Version {
+    let (major, minor, patch) = match level {
+        BumpLevel::Major => (version.major + 1, 0, 0),
+        BumpLevel::Minor => (version.major, version.minor + 1, 0),
+        BumpLevel::Patch if !version.pre.is_empty() => {
+            (version.major, version.minor, version.patch)
+        }
+        BumpLevel::Patch => (version.major, version.minor, version.patch + 1),
+    };
+
+    semver::Version::new(major, minor, patch)
+}
+
+/// Changes the version requirements of all path dependencies of a Cargo.toml that point
+/// to one of the packages in `versions`, which maps their absolute paths to their new
+/// versions. This includes the entries of `[workspace.dependencies]` in the root Cargo.toml.
+/// Dependencies without a version requirement are left alone.
+/// Returns the keys of the changed dependencies.
+fn update_version_requirements(
+    manifest: &mut ManifestDocument,
+    manifest_dir: &Path,
+    versions: &[(PathBuf, String)],
+) -> Vec<String> {
+    let mut updated = Vec::new();
+
+    if let Some(deps) = manifest.workspace_dependencies_mut() {
+        update_table(deps, manifest_dir, versions, &mut updated);
+    }
+    for (_, deps) in manifest.dependency_tables_mut() {
+        update_table(deps, manifest_dir, versions, &mut updated);
+    }
+
+    updated
+}
+
+/// Changes the version requirements of the dependencies in one table,
+/// see [update_version_requirements]. The changed keys are added to `updated`.
+fn update_table(
+    deps: &mut dyn TableLike,
+    manifest_dir: &Path,
+    versions: &[(PathBuf, String)],
+    updated: &mut Vec<String>,
+) {
+    for (key, dep) in deps.iter_mut() {
+        let path = match dep.get("path").and_then(|p| p.as_str()) {
+            Some(p) => fs::normalize_path(&manifest_dir.join(p)),
+            None => continue,
+        };
+
+        let version = match versions.iter().find(|(p, _)| *p == path) {
+            Some((_, v)) => v,
+            None => continue,
+        };
+
+        let requirement = match dep
+            .as_table_like_mut()
+            .and_then(|d| d.get_mut("version"))
+            .and_then(|v| v.as_value_mut())
+        {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let new_requirement = match requirement.as_str() {
+            Some(old) => requirement_for(old, version),
+            None => continue,
+        };
+
+        let decor = requirement.decor().clone();
+        *requirement = new_requirement.into();
+        *requirement.decor_mut() = decor;
+        updated.push(key.get().to_owned());
+    }
+}
+
+/// Creates a requirement for `version`, keeping the operator of the old requirement
+/// if it still makes sense, like in `=1.2.0` or `~1.2`.
+fn requirement_for(old: &str, version: &str) -> String {
+    let old = old.trim();
+    if old.contains(',') {
+        return version.to_owned();
+    }
+
+    let operator = [">=", "=", "^", "~"]
+        .into_iter()
+        .find(|op| old.starts_with(op))
+        .unwrap_or("");
+
+    format!("{}{}", operator, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bumped() {
+        let v = |s: &str| semver::Version::parse(s).unwrap();
+
+        assert_eq!(bumped(&v("1.2.3"), BumpLevel::Patch), v("1.2.4"));
+        assert_eq!(bumped(&v("1.2.3"), BumpLevel::Minor), v("1.3.0"));
+        assert_eq!(bumped(&v("1.2.3+b"), BumpLevel::Major), v("2.0.0"));
+        assert_eq!(bumped(&v("1.3.0-rc.1"), BumpLevel::Patch), v("1.3.0"));
+    }
+
+    #[test]
+    fn test_requirement_for() {
+        assert_eq!(requirement_for("0.1", "0.2.0"), "0.2.0");
+        assert_eq!(requirement_for("=0.1.0", "0.2.0"), "=0.2.0");
+        assert_eq!(requirement_for("~0.1", "0.2.0"), "~0.2.0");
+        assert_eq!(requirement_for(">=0.1, <0.2", "0.2.0"), "0.2.0");
+        assert_eq!(requirement_for("<0.2", "0.2.0"), "0.2.0");
+    }
+
+    #[test]
+    fn test_update_version_requirements() {
+        let mut manifest = ManifestDocument::parse(
+            PathBuf::from("app/Cargo.toml"),
+            "\
+[dependencies]
+core = { path = \"../core\", version = \"=0.1.0\" } # pinned
+util = { path = \"../util\" }
+serde = \"1.0\"
+
+[dev-dependencies.core]
+path = \"../core\"
+version = \"0.1\"
+",
+        )
+        .unwrap();
+
+        let versions = vec![
+            (PathBuf::from("core"), String::from("0.2.0")),
+            (PathBuf::from("util"), String::from("1.0.0")),
+        ];
+
+        let updated = update_version_requirements(&mut manifest, Path::new("./app"), &versions);
+
+        assert_eq!(updated, vec![String::from("core"), String::from("core")]);
+        assert_eq!(
+            manifest.to_string(),
+            "\
+[dependencies]
+core = { path = \"../core\", version = \"=0.2.0\" } # pinned
+util = { path = \"../util\" }
+serde = \"1.0\"
+
+[dev-dependencies.core]
+path = \"../core\"
+version = \"0.2.0\"
+"
+        );
+
+        // Paths in [workspace.dependencies] are relative to the root.
+        let mut root = ManifestDocument::parse(
+            PathBuf::from("Cargo.toml"),
+            "\
+[workspace]
+members = [\"core\", \"app\"]
+
+[workspace.dependencies]
+core = { path = \"core\", version = \"0.1.0\" }
+
+[dependencies]
+core = { workspace = true }
+",
+        )
+        .unwrap();
+
+        let updated = update_version_requirements(&mut root, Path::new("."), &versions);
+
+        assert_eq!(updated, vec![String::from("core")]);
+        assert!(root
+            .to_string()
+            .contains("core = { path = \"core\", version = \"0.2.0\" }"));
+    }
+}
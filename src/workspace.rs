@@ -1,15 +1,362 @@
-use crate::crates::Crate;
-use crate::fs::{self, CargoToml, PackageSection, WorkspaceSection};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CrateKind, Defaults, New, RootKind, TargetSpec, Targets};
+use crate::crates::{self, Crate};
+use crate::fs::{
+    self, CargoToml, Dependency, IOError, Manifest, ManifestWorkspacePackage, PackageSection,
+    WorkspacePackageSection, WorkspaceSection, WorkspaceSettings,
+};
+use crate::git::{self, GitError};
+use crate::graph::DependencyGraph;
 use crate::input;
+use crate::package::{DependencyKind, Package, TargetKind};
 
 /// This struct creates a new Workspace with crates and
 /// a workspace-level Cargo.toml, based on user input.
+///
+/// It can also be (de)serialized, which is used for blueprints.
+#[derive(Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct Workspace {
+    #[serde(skip)]
     pub project_name: String,
+
+    #[serde(skip)]
     pub directory_name: String,
+
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+
+    #[serde(rename = "root", default, skip_serializing_if = "Option::is_none")]
     pub root_crate: Option<Crate>,
+
+    #[serde(rename = "members", default)]
     pub crates: Vec<Crate>,
+
+    /// Absolute path of the root directory, if the workspace was loaded from disk.
+    #[serde(skip)]
+    pub root_path: PathBuf,
+
+    /// All packages of the workspace, if it was loaded from disk.
+    #[serde(skip)]
+    pub packages: Vec<Package>,
+
+    /// Settings from the root Cargo.toml, if the workspace was loaded from disk.
+    #[serde(skip)]
+    pub settings: WorkspaceSettings,
+}
+
+/// This enum contains all errors that can occur when loading a workspace.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum LoadError {
+    /// No Cargo.toml with a \[workspace\] section was found in the directory or its parents.
+    NotFound(PathBuf),
+
+    /// A Cargo.toml could not be read.
+    Manifest(PathBuf, IOError),
+
+    /// An entry of `[workspace].members` is not a valid glob pattern.
+    InvalidPattern(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotFound(p) => write!(
+                f,
+                "could not find a workspace in {} or any of its parent directories",
+                p.display()
+            ),
+            LoadError::Manifest(p, e) => write!(f, "reading {} failed: {}", p.display(), e),
+            LoadError::InvalidPattern(p) => write!(f, "invalid member pattern {}", p),
+        }
+    }
+}
+
+/// Reads and deserializes a Cargo.toml.
+fn read_manifest(path: &Path) -> Result<Manifest, LoadError> {
+    match fs::read_toml_file::<Manifest>(path) {
+        Ok(m) => Ok(m),
+        Err(e) => Err(LoadError::Manifest(path.to_path_buf(), e)),
+    }
+}
+
+/// Expands an entry of `[workspace].members`, which can be a glob pattern,
+/// into the directories of the matching crates, relative to `root_path`.
+fn expand_member(root_path: &Path, member: &str) -> Result<Vec<PathBuf>, LoadError> {
+    if !member.contains(['*', '?', '[']) {
+        return Ok(vec![fs::normalize_path(Path::new(member))]);
+    }
+
+    let pattern = root_path.join(member);
+    let paths = match glob::glob(&pattern.to_string_lossy()) {
+        Ok(p) => p,
+        Err(_) => return Err(LoadError::InvalidPattern(member.to_owned())),
+    };
+
+    Ok(paths
+        .filter_map(|p| p.ok())
+        .filter(|p| p.join("Cargo.toml").is_file())
+        .filter_map(|p| p.strip_prefix(root_path).ok().map(fs::normalize_path))
+        .collect())
+}
+
+/// Checks whether one of the glob patterns in `members` matches `directory`,
+/// which is relative to the root of the workspace.
+pub fn matches_member_pattern(members: &[String], directory: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::default()
+    };
+
+    members
+        .iter()
+        .filter(|m| m.contains(['*', '?', '[']))
+        .any(|m| Pattern::new(m).is_ok_and(|p| p.matches_path_with(directory, options)))
+}
+
+/// Combines the directories of the members into one glob pattern like `crates/*`
+/// if all of them are directly inside the same directory, which is not the root.
+/// Used instead of listing every member if `glob-members` is set.
+fn member_patterns(members: Vec<String>) -> Vec<String> {
+    let parent = members.first().and_then(|m| Path::new(m).parent());
+    let pattern = match parent {
+        Some(p)
+            if !p.as_os_str().is_empty()
+                && members.iter().all(|m| Path::new(m).parent() == Some(p)) =>
+        {
+            Some(format!("{}/*", p.display()))
+        }
+        _ => None,
+    };
+
+    match pattern {
+        Some(p) => vec![p],
+        None => members,
+    }
+}
+
+/// Describes a loaded package by the kind of crate it is and its other targets. A package
+/// with a library and a binary named like the package is a binary crate with a library.
+fn crate_kind_and_targets(package: &Package) -> (CrateKind, Targets) {
+    let has_target = |kind: TargetKind, name: &str| {
+        package
+            .targets
+            .iter()
+            .any(|t| t.kind == kind && t.name == name)
+    };
+    let kind = if package
+        .targets
+        .iter()
+        .any(|t| t.kind == TargetKind::ProcMacro)
+    {
+        CrateKind::ProcMacro
+    } else if package.is_lib() && !has_target(TargetKind::Bin, &package.name) {
+        CrateKind::Lib
+    } else {
+        CrateKind::Bin
+    };
+
+    let mut targets = Targets {
+        lib: kind == CrateKind::Bin && package.is_lib(),
+        ..Targets::default()
+    };
+    for t in &package.targets {
+        let list = match t.kind {
+            TargetKind::Bin if kind == CrateKind::Bin && t.name == package.name => continue,
+            TargetKind::Bin => &mut targets.bins,
+            TargetKind::Example => &mut targets.examples,
+            TargetKind::Test => &mut targets.tests,
+            TargetKind::Bench => &mut targets.benches,
+            TargetKind::Lib | TargetKind::ProcMacro => continue,
+        };
+        let path = t.path.to_string_lossy().replace('\\', "/");
+        list.push(TargetSpec {
+            name: t.name.clone(),
+            path: (path != crates::default_target_path(t.kind, &t.name, &package.name))
+                .then_some(path),
+        });
+    }
+
+    (kind, targets)
+}
+
+/// Package metadata shared by all crates of a workspace.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+
+    /// Whether the metadata is written to \[workspace.package\] and inherited by the crates.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inherit: bool,
+}
+
+impl Metadata {
+    /// Whether no metadata is set at all.
+    pub fn is_empty(&self) -> bool {
+        self.version.is_none()
+            && self.edition.is_none()
+            && self.authors.is_empty()
+            && self.license.is_none()
+            && self.repository.is_none()
+            && !self.inherit
+    }
+
+    /// Fills in the version, edition, license and authors that are not set from `defaults`.
+    pub fn apply_defaults(&mut self, defaults: &Defaults) {
+        fn fill(value: &mut Option<String>, default: &Option<String>) {
+            if value.is_none() {
+                *value = default.clone();
+            }
+        }
+        fill(&mut self.version, &defaults.version);
+        fill(&mut self.edition, &defaults.edition);
+        fill(&mut self.license, &defaults.license);
+        if self.authors.is_empty() {
+            self.authors = defaults.authors.clone().unwrap_or_default();
+        }
+    }
+
+    /// Edition of the crates, which is also used for new crates.
+    fn edition(&self) -> &str {
+        self.edition.as_deref().unwrap_or("2021")
+    }
+
+    /// Returns the version of the dependency resolver that belongs to the edition.
+    pub fn resolver(&self) -> &'static str {
+        match self.edition() {
+            "2015" | "2018" => "1",
+            "2021" => "2",
+            _ => "3",
+        }
+    }
+
+    /// Creates the \[package\] section for the crate `name`. If the metadata is
+    /// inherited, all keys that are set in \[workspace.package\] refer to it.
+    pub fn package_section<'a>(&'a self, name: &'a str) -> PackageSection<'a> {
+        let mut inherited = Vec::new();
+        if self.inherit {
+            let keys = [
+                ("version", self.version.is_some()),
+                ("edition", self.edition.is_some()),
+                ("authors", !self.authors.is_empty()),
+                ("license", self.license.is_some()),
+                ("repository", self.repository.is_some()),
+            ];
+            inherited = keys
+                .iter()
+                .filter(|(_, set)| *set)
+                .map(|(k, _)| *k)
+                .collect();
+        }
+
+        PackageSection {
+            pkg_name: name,
+            pkg_version: self.version.as_deref().unwrap_or("0.1.0"),
+            pkg_edition: self.edition(),
+            pkg_authors: &self.authors,
+            pkg_license: self.license.as_deref(),
+            pkg_repository: self.repository.as_deref(),
+            pkg_inherited: inherited,
+        }
+    }
+
+    /// Creates the \[workspace.package\] section, if the metadata is inherited.
+    pub fn workspace_package_section(&self) -> Option<WorkspacePackageSection<'_>> {
+        match self.inherit {
+            true => Some(WorkspacePackageSection {
+                version: self.version.as_deref(),
+                edition: self.edition.as_deref(),
+                authors: &self.authors,
+                license: self.license.as_deref(),
+                repository: self.repository.as_deref(),
+            }),
+            false => None,
+        }
+    }
+
+    /// Fills in the default version and edition, so that they
+    /// are shared by all crates when the metadata is inherited.
+    fn with_defaults(&self) -> Metadata {
+        let mut metadata = self.clone();
+        metadata
+            .version
+            .get_or_insert_with(|| String::from("0.1.0"));
+        metadata.edition.get_or_insert_with(|| String::from("2021"));
+        metadata
+    }
+
+    /// Reads the metadata from a deserialized Cargo.toml. Values inherited
+    /// from the workspace are taken from `inherited`.
+    fn from_manifest(
+        manifest: &Manifest,
+        inherited: Option<&ManifestWorkspacePackage>,
+    ) -> Metadata {
+        let p = match &manifest.package {
+            Some(p) => p,
+            None => return Metadata::default(),
+        };
+
+        Metadata {
+            version: p
+                .version
+                .as_ref()
+                .and_then(|v| v.resolve(inherited.and_then(|i| i.version.as_ref())))
+                .cloned(),
+            edition: p
+                .edition
+                .as_ref()
+                .and_then(|v| v.resolve(inherited.and_then(|i| i.edition.as_ref())))
+                .cloned(),
+            authors: p
+                .authors
+                .as_ref()
+                .and_then(|v| v.resolve(inherited.and_then(|i| i.authors.as_ref())))
+                .cloned()
+                .unwrap_or_default(),
+            license: p
+                .license
+                .as_ref()
+                .and_then(|v| v.resolve(inherited.and_then(|i| i.license.as_ref())))
+                .cloned(),
+            repository: p
+                .repository
+                .as_ref()
+                .and_then(|v| v.resolve(inherited.and_then(|i| i.repository.as_ref())))
+                .cloned(),
+            inherit: false,
+        }
+    }
+
+    /// Reads the metadata that is inherited by the crates from \[workspace.package\].
+    fn from_workspace_package(package: &ManifestWorkspacePackage) -> Metadata {
+        Metadata {
+            version: package.version.clone(),
+            edition: package.edition.clone(),
+            authors: package.authors.clone().unwrap_or_default(),
+            license: package.license.clone(),
+            repository: package.repository.clone(),
+            inherit: true,
+        }
+    }
 }
 
 impl Workspace {
@@ -21,23 +368,366 @@ impl Workspace {
             } else {
                 project_name.to_owned()
             },
+            metadata: Metadata::default(),
             root_crate: None,
             crates: Vec::new(),
+            root_path: PathBuf::new(),
+            packages: Vec::new(),
+            settings: WorkspaceSettings::default(),
         }
     }
 
-    pub fn fill_from_user_input(&mut self) {
-        self.root_crate = {
-            if input::prompt_yes_no("Add root crate?", input::DefaultBool::Yes).unwrap() {
-                println!("\nPlease specify some information about the root crate:");
-                let r_crate = Some(Crate::new_from_user_input(true, false, true));
-                println!();
-                r_crate
+    /// Searches `path` and its parent directories for the root
+    /// Cargo.toml of a workspace and returns the directory it is in.
+    pub fn find_root(path: &Path) -> Result<PathBuf, LoadError> {
+        let start = match fs::absolute_path(path) {
+            Ok(p) => p,
+            Err(e) => return Err(LoadError::Manifest(path.to_path_buf(), e)),
+        };
+
+        for dir in start.ancestors() {
+            let manifest_path = dir.join("Cargo.toml");
+            if !manifest_path.is_file() {
+                continue;
+            }
+
+            match fs::read_toml_file::<Manifest>(&manifest_path) {
+                Ok(m) if m.workspace.is_some() => return Ok(dir.to_path_buf()),
+                Ok(_) => {}
+                Err(e) => return Err(LoadError::Manifest(manifest_path, e)),
+            }
+        }
+
+        Err(LoadError::NotFound(start))
+    }
+
+    /// Loads the workspace that `path` belongs to. The root of the workspace is
+    /// searched with [Workspace::find_root], then the Cargo.toml of every member is read.
+    pub fn load(path: &Path) -> Result<Workspace, LoadError> {
+        let root_path = Workspace::find_root(path)?;
+        let root_manifest = read_manifest(&root_path.join("Cargo.toml"))?;
+        let ws_section = root_manifest.workspace.as_ref();
+
+        let mut directories = Vec::new();
+        if root_manifest.package.is_some() {
+            directories.push(PathBuf::from("."));
+        }
+        for member in ws_section.map(|w| &w.members).into_iter().flatten() {
+            for dir in expand_member(&root_path, member)? {
+                if !directories.contains(&dir) {
+                    directories.push(dir);
+                }
+            }
+        }
+
+        let excluded: Vec<PathBuf> = ws_section
+            .map(|w| &w.exclude)
+            .into_iter()
+            .flatten()
+            .map(|e| fs::normalize_path(Path::new(e)))
+            .collect();
+        directories.retain(|d| !excluded.iter().any(|e| d.starts_with(e)));
+
+        let mut manifests = Vec::new();
+        for dir in directories {
+            let manifest = if dir == Path::new(".") {
+                read_manifest(&root_path.join("Cargo.toml"))?
             } else {
-                None
+                read_manifest(&root_path.join(&dir).join("Cargo.toml"))?
+            };
+            manifests.push((dir, manifest));
+        }
+
+        let resolve = |path: &Path| {
+            let path = fs::normalize_path(path);
+            manifests
+                .iter()
+                .find(|(dir, _)| fs::normalize_path(dir) == path)
+                .and_then(|(_, m)| m.package.as_ref())
+                .map(|p| p.name.clone())
+        };
+
+        let inherited = ws_section.and_then(|w| w.package.as_ref());
+        let packages: Vec<Package> = manifests
+            .iter()
+            .filter_map(|(dir, m)| Package::from_manifest(m, &root_path, dir, ws_section, resolve))
+            .collect();
+
+        let mut ws = Workspace {
+            project_name: match &root_manifest.package {
+                Some(p) => p.name.clone(),
+                None => root_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            },
+            directory_name: root_path.to_string_lossy().into_owned(),
+            metadata: match inherited {
+                Some(p) => Metadata::from_workspace_package(p),
+                None => Metadata::from_manifest(
+                    match &root_manifest.package {
+                        Some(_) => &root_manifest,
+                        None => manifests.first().map_or(&root_manifest, |(_, m)| m),
+                    },
+                    inherited,
+                ),
+            },
+            root_crate: None,
+            crates: Vec::new(),
+            root_path,
+            packages,
+            settings: ws_section
+                .map(|w| w.metadata.ws_manage.clone())
+                .unwrap_or_default(),
+        };
+        ws.fill_from_packages();
+
+        Ok(ws)
+    }
+
+    /// Loads the workspace by using [Workspace::load], but errors will be handled
+    /// by displaying an error message and exiting the process with code 1.
+    pub fn load_or_handle_error(path: &Path) -> Workspace {
+        match Workspace::load(path) {
+            Ok(ws) => ws,
+            Err(e) => {
+                println!("Loading the workspace failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Describes the loaded packages with a root crate and member crates,
+    /// like they are used for creating a workspace or a blueprint.
+    fn fill_from_packages(&mut self) {
+        let root = self.root_package();
+
+        let root_crate = root.map(|p| {
+            let (kind, targets) = crate_kind_and_targets(p);
+            let mut c = Crate::new_root(kind);
+            c.targets = targets;
+            c
+        });
+        let crates = self
+            .members()
+            .map(|p| {
+                let (kind, targets) = crate_kind_and_targets(p);
+                let mut c = Crate::new_member(
+                    &p.name,
+                    &p.directory.to_string_lossy(),
+                    kind,
+                    root.is_some_and(|r| r.depends_on(&p.name)),
+                );
+                c.targets = targets;
+                c.dependencies = p
+                    .dependencies
+                    .iter()
+                    .filter(|d| d.kind == DependencyKind::Normal && d.platform.is_none())
+                    .map(|d| d.package.clone())
+                    .collect();
+                c
+            })
+            .collect();
+
+        self.root_crate = root_crate;
+        self.crates = crates;
+    }
+
+    /// Returns the package in the root directory of the workspace, if there is one.
+    pub fn root_package(&self) -> Option<&Package> {
+        self.packages.iter().find(|p| p.directory == Path::new("."))
+    }
+
+    /// Returns all packages except the one in the root directory.
+    pub fn members(&self) -> impl Iterator<Item = &Package> {
+        self.packages
+            .iter()
+            .filter(|p| p.directory != Path::new("."))
+    }
+
+    /// Returns the package called `name`.
+    pub fn package(&self, name: &str) -> Option<&Package> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    /// Returns all packages which have a path dependency on the package `name`.
+    pub fn dependents(&self, name: &str) -> Vec<&Package> {
+        self.packages
+            .iter()
+            .filter(|p| p.dependencies.iter().any(|d| d.package == name))
+            .collect()
+    }
+
+    /// Returns the graph of the dependencies between the packages.
+    pub fn graph(&self) -> DependencyGraph<'_> {
+        DependencyGraph::new(&self.packages)
+    }
+
+    /// Returns the absolute path of a package's directory.
+    pub fn package_path(&self, package: &Package) -> PathBuf {
+        fs::normalize_path(&self.root_path.join(&package.directory))
+    }
+
+    /// Returns the package a file belongs to, which is the one with the
+    /// innermost directory containing it. `path` is relative to the root.
+    pub fn owning_package(&self, path: &Path) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p| p.directory == Path::new(".") || path.starts_with(&p.directory))
+            .max_by_key(|p| p.directory.components().count())
+    }
+
+    /// Returns the packages containing files that were changed
+    /// since the git reference `since`, in the order of [Workspace::packages].
+    pub fn changed_packages(&self, since: &str) -> Result<Vec<&Package>, GitError> {
+        let files = git::changed_files(&self.root_path, since)?;
+        let changed: Vec<&str> = files
+            .iter()
+            .filter_map(|f| self.owning_package(f))
+            .map(|p| p.name.as_str())
+            .collect();
+
+        Ok(self
+            .packages
+            .iter()
+            .filter(|p| changed.contains(&p.name.as_str()))
+            .collect())
+    }
+
+    /// Returns the packages affected by the changes since the git reference `since`,
+    /// which are the changed packages and everything that depends on them, directly
+    /// or transitively. Every package is returned together with whether it was changed
+    /// itself, in the order of [Workspace::packages].
+    pub fn affected_packages(&self, since: &str) -> Result<Vec<(&Package, bool)>, GitError> {
+        let changed = self.changed_packages(since)?;
+
+        let mut affected: Vec<&str> = changed.iter().map(|p| p.name.as_str()).collect();
+        let mut i = 0;
+        while i < affected.len() {
+            for dependent in self.dependents(affected[i]) {
+                if !affected.contains(&dependent.name.as_str()) {
+                    affected.push(&dependent.name);
+                }
             }
+            i += 1;
+        }
+
+        Ok(self
+            .packages
+            .iter()
+            .filter(|p| affected.contains(&p.name.as_str()))
+            .map(|p| (p, changed.iter().any(|c| c.name == p.name)))
+            .collect())
+    }
+
+    /// Fills in the root crate and the member crates. Everything that is not specified
+    /// by `cfg` is taken from `defaults` or asked from the user, unless `cfg.yes` is set.
+    pub fn fill_from_config(&mut self, cfg: &New, defaults: &Defaults) {
+        self.settings.defaults.members_dir = defaults.members_dir.clone();
+        self.settings.defaults.glob_members = defaults.glob_members;
+        self.metadata.inherit = cfg.workspace_package;
+
+        self.root_crate = match &cfg.root {
+            Some(RootKind::Bin) => Some(Crate::new_root(CrateKind::Bin)),
+            Some(RootKind::Lib) => Some(Crate::new_root(CrateKind::Lib)),
+            Some(RootKind::None) => None,
+            None if cfg.yes => Some(Crate::new_root(CrateKind::Bin)),
+            None => Workspace::root_crate_from_user_input(defaults),
+        };
+
+        self.crates = if !cfg.members.is_empty() {
+            cfg.members
+                .iter()
+                .map(|m| {
+                    let directory = match &m.directory {
+                        Some(d) => d.clone(),
+                        None => {
+                            let members_dir = self.settings.defaults.members_dir.as_deref();
+                            crates::default_directory(&m.name, members_dir)
+                        }
+                    };
+                    let kind = m.kind.or(defaults.crate_kind).unwrap_or_default();
+                    let mut c = Crate::new_member(&m.name, &directory, kind, m.as_dependency);
+                    c.derive = m.derive;
+                    c.targets = m.targets.clone();
+                    c.dependencies = m.dependencies.clone();
+                    c.template = m.template.clone();
+                    c
+                })
+                .collect()
+        } else if cfg.yes {
+            Vec::new()
+        } else {
+            self.member_crates_from_user_input(defaults)
+        };
+    }
+
+    /// Adds the derive crates of the member crates that ask for one after them, unless
+    /// there is a member crate with the same name already, and lets them depend on it.
+    pub fn add_derive_crates(&mut self) {
+        let mut i = 0;
+        while i < self.crates.len() {
+            if self.crates[i].derive {
+                let name = self.crates[i].derive_crate_name();
+                if !self.crates.iter().any(|c| c.crate_name == name) {
+                    let derive_crate = self.crates[i].derive_crate();
+                    self.crates.insert(i + 1, derive_crate);
+                }
+                if !self.crates[i].dependencies.contains(&name) {
+                    self.crates[i].dependencies.push(name);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    /// Sets the directory of the user templates, which is given relative to the
+    /// current directory and stored relative to the root of the workspace.
+    pub fn set_template_dir(&mut self, dir: &str) {
+        let dir = match Path::new(dir).is_absolute() {
+            true => PathBuf::from(dir),
+            false => fs::relative_path(Path::new(&self.directory_name), Path::new(dir)),
         };
+        self.settings.defaults.template_dir = Some(dir.to_string_lossy().into_owned());
+    }
+
+    /// Loads the templates of all crates, so that unknown or broken templates are
+    /// reported before anything is written. Without a template directory set by
+    /// [Workspace::set_template_dir], the templates are looked up in `fallback_dir`.
+    pub fn load_templates(&mut self, fallback_dir: Option<&str>) -> Result<(), String> {
+        let template_dir = match &self.settings.defaults.template_dir {
+            Some(d) => Some(fs::normalize_path(&Path::new(&self.directory_name).join(d))),
+            None => fallback_dir.map(PathBuf::from),
+        };
+
+        for c in self.root_crate.iter_mut().chain(self.crates.iter_mut()) {
+            c.load_template(template_dir.as_deref())?;
+        }
+
+        Ok(())
+    }
 
+    /// Takes the root crate, the member crates and the metadata from a blueprint.
+    pub fn fill_from_blueprint(&mut self, blueprint: Workspace) {
+        self.metadata = blueprint.metadata;
+        self.root_crate = blueprint.root_crate;
+        self.crates = blueprint.crates;
+    }
+
+    fn root_crate_from_user_input(defaults: &Defaults) -> Option<Crate> {
+        if input::prompt_yes_no("Add root crate?", input::DefaultBool::Yes).unwrap() {
+            println!("\nPlease specify some information about the root crate:");
+            let r_crate = Some(Crate::new_from_user_input(
+                true, false, true, None, None, None, defaults,
+            ));
+            println!();
+            r_crate
+        } else {
+            None
+        }
+    }
+
+    fn member_crates_from_user_input(&self, defaults: &Defaults) -> Vec<Crate> {
         let mut crates = Vec::new();
         while input::prompt_yes_no(
             "Do you want to add a/another member crate?",
@@ -46,45 +736,196 @@ impl Workspace {
         .unwrap()
         {
             println!("\nPlease specify some information about this crate:");
-            crates.push(Crate::new_from_user_input(false, true, true));
+            let mut c = Crate::new_from_user_input(
+                false,
+                self.root_crate.is_some(),
+                true,
+                None,
+                None,
+                None,
+                defaults,
+            );
+            c.derive_from_user_input(true);
+            c.dependencies = Workspace::member_dependencies_from_user_input(&crates);
+            crates.push(c);
             println!();
         }
 
-        self.crates = crates;
+        crates
+    }
+
+    /// Asks which of the library crates added so far the next member crate depends on.
+    fn member_dependencies_from_user_input(earlier: &[Crate]) -> Vec<String> {
+        let libraries: Vec<&str> = earlier
+            .iter()
+            .filter(|c| c.has_lib())
+            .map(|c| c.crate_name.as_str())
+            .collect();
+        if libraries.is_empty() {
+            return Vec::new();
+        }
+
+        loop {
+            let answer = input::get_string(
+                &format!(
+                    "    Which of these crates does it depend on ({})? Separate them by commas.",
+                    libraries.join(", ")
+                ),
+                None,
+                Some(true),
+            );
+            let chosen: Vec<String> = answer
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(str::to_owned)
+                .collect();
+
+            match chosen.iter().find(|d| !libraries.contains(&d.as_str())) {
+                Some(d) => println!("    {} is not one of the library crates above", d),
+                None => return chosen,
+            }
+        }
+    }
+
+    /// Checks whether the workspace can be written to disk.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.root_crate.as_ref().is_some_and(|r| r.derive) {
+            return Err(String::from("The root crate cannot have a derive crate"));
+        }
+        for c in self.root_crate.iter().chain(self.crates.iter()) {
+            c.validate_targets(&self.project_name)?;
+        }
+
+        for (i, c) in self.crates.iter().enumerate() {
+            if c.crate_name.is_empty() {
+                return Err(String::from("A member crate has no name"));
+            }
+
+            if c.as_dependency && self.root_crate.is_none() {
+                return Err(format!(
+                    "{} is marked as dependency of the root crate, but there is no root crate",
+                    c.crate_name
+                ));
+            }
+
+            if c.derive && !c.can_have_derive_crate() {
+                return Err(format!(
+                    "{} has a derive crate, but is not a library crate",
+                    c.crate_name
+                ));
+            }
+
+            for dep in &c.dependencies {
+                if dep == &c.crate_name {
+                    return Err(format!("{} depends on itself", c.crate_name));
+                }
+                match self.crates.iter().find(|other| &other.crate_name == dep) {
+                    None => {
+                        return Err(format!(
+                            "{} depends on {}, which is not a member crate",
+                            c.crate_name, dep
+                        ))
+                    }
+                    Some(other) if !other.has_lib() => {
+                        return Err(format!(
+                            "{} depends on {}, which is not a library crate",
+                            c.crate_name, dep
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            for other in &self.crates[..i] {
+                if other.crate_name == c.crate_name {
+                    return Err(format!("There are multiple crates named {}", c.crate_name));
+                }
+                if other.directory_name == c.directory_name {
+                    return Err(format!(
+                        "There are multiple crates in the directory {}",
+                        c.directory_name
+                    ));
+                }
+            }
+        }
+
+        // The dependencies are described like the ones of loaded packages, so cycles
+        // are found the same way as for existing workspaces.
+        let packages: Vec<Package> = self.crates.iter().map(Crate::as_package).collect();
+        if let Err(e) = DependencyGraph::new(&packages).waves() {
+            return Err(format!("The member crates contain a {}", e));
+        }
+
+        Ok(())
     }
 
     pub fn write_to_disk(&self) {
         fs::create_dir_or_handle_error(&self.directory_name);
 
-        let mut deps = Vec::<String>::new();
+        let mut deps = Vec::<Dependency>::new();
         let mut members = Vec::<String>::new();
+        let metadata = match self.metadata.inherit {
+            true => self.metadata.with_defaults(),
+            false => self.metadata.clone(),
+        };
 
         for member_crate in &self.crates {
-            member_crate.write_to_disk(&self.directory_name);
+            member_crate.write_to_disk(
+                &self.directory_name,
+                &self.project_name,
+                &self.member_dependencies(member_crate),
+                &metadata,
+            );
 
-            members.push(member_crate.crate_name.clone());
+            // Members and path dependencies refer to the directory, which can differ from the name.
+            let directory =
+                fs::relative_path(Path::new("."), Path::new(&member_crate.directory_name))
+                    .to_string_lossy()
+                    .into_owned();
+
+            members.push(directory.clone());
             if member_crate.as_dependency {
-                deps.push(member_crate.crate_name.clone());
+                deps.push(Dependency::path(&member_crate.crate_name, &directory));
             }
         }
 
-        self.write_root_crate(&deps, &members);
+        if self.settings.defaults.glob_members == Some(true) {
+            members = member_patterns(members);
+        }
+        self.write_root_crate(&deps, &members, &metadata);
     }
 
-    fn write_root_crate(&self, deps: &Vec<String>, members: &Vec<String>) {
+    /// Collects the dependencies of a member crate on other member crates.
+    fn member_dependencies(&self, member_crate: &Crate) -> Vec<Dependency> {
+        member_crate
+            .dependencies
+            .iter()
+            .filter_map(|name| self.crates.iter().find(|c| &c.crate_name == name))
+            .map(|dep| {
+                let path = fs::relative_path(
+                    Path::new(&member_crate.directory_name),
+                    Path::new(&dep.directory_name),
+                );
+                Dependency::path(&dep.crate_name, &path.to_string_lossy())
+            })
+            .collect()
+    }
+
+    fn write_root_crate(&self, deps: &Vec<Dependency>, members: &Vec<String>, metadata: &Metadata) {
         if let Some(c) = &self.root_crate {
-            c.write_to_disk(&self.directory_name);
+            c.write_to_disk(&self.directory_name, &self.project_name, deps, metadata);
         }
+        let template = self
+            .root_crate
+            .as_ref()
+            .and_then(|c| c.template_manifest(&self.project_name, metadata));
 
         fs::write_cargo_toml_or_handle_error(
             &self.directory_name,
             &CargoToml {
                 package: if self.root_crate.is_some() {
-                    Some(PackageSection {
-                        pkg_name: &self.project_name,
-                        pkg_version: "0.1.0",
-                        pkg_edition: "2021",
-                    })
+                    Some(metadata.package_section(&self.project_name))
                 } else {
                     None
                 },
@@ -93,8 +934,231 @@ impl Workspace {
                 } else {
                     None
                 },
-                workspace: Some(WorkspaceSection { members }),
+                targets: self
+                    .root_crate
+                    .as_ref()
+                    .map(|c| c.target_sections(&self.project_name))
+                    .unwrap_or_default(),
+                workspace: Some(WorkspaceSection {
+                    members,
+                    resolver: metadata.resolver(),
+                    package: metadata.workspace_package_section(),
+                    members_dir: self.settings.defaults.members_dir.as_deref(),
+                    template_dir: self.settings.defaults.template_dir.as_deref(),
+                }),
+                template: template.as_ref(),
             },
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VersionMode;
+    use crate::fs::context_setup::TestFiles;
+    use test_context::test_context;
+
+    fn write(ctx: &TestFiles, path: &str, content: &str) {
+        let path = Path::new(&ctx.name).join(path);
+        fs::mkdir(path.parent().unwrap(), true).unwrap();
+        fs::write_file(&path, &String::from(content), true).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_inheritance() {
+        let metadata = Metadata {
+            license: Some(String::from("MIT")),
+            inherit: true,
+            ..Metadata::default()
+        };
+        assert_eq!(metadata.resolver(), "2");
+        assert_eq!(metadata.package_section("a").pkg_inherited, vec!["license"]);
+        assert_eq!(
+            metadata.with_defaults().package_section("a").pkg_inherited,
+            vec!["version", "edition", "license"]
+        );
+
+        let metadata = Metadata {
+            edition: Some(String::from("2018")),
+            ..Metadata::default()
+        };
+        assert_eq!(metadata.resolver(), "1");
+        assert!(metadata.package_section("a").pkg_inherited.is_empty());
+        assert!(metadata.workspace_package_section().is_none());
+    }
+
+    #[test]
+    fn test_member_patterns() {
+        let members = |m: &[&str]| m.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(
+            member_patterns(members(&["crates/a", "crates/b"])),
+            vec!["crates/*"]
+        );
+        assert_eq!(
+            member_patterns(members(&["crates/a", "libs/b"])),
+            vec!["crates/a", "libs/b"]
+        );
+        assert_eq!(member_patterns(members(&["a", "b"])), vec!["a", "b"]);
+        assert!(member_patterns(Vec::new()).is_empty());
+
+        assert!(matches_member_pattern(
+            &members(&["app", "crates/*"]),
+            Path::new("crates/core")
+        ));
+        assert!(!matches_member_pattern(
+            &members(&["app", "crates/*"]),
+            Path::new("crates/core/inner")
+        ));
+        assert!(!matches_member_pattern(
+            &members(&["app"]),
+            Path::new("app")
+        ));
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_load(ctx: &mut TestFiles) {
+        write(
+            ctx,
+            "Cargo.toml",
+            "\
+[package]
+name = \"demo\"
+version.workspace = true
+
+[dependencies]
+core = { workspace = true }
+
+[workspace]
+members = [\"crates/*\"]
+exclude = [\"crates/old\"]
+
+[workspace.package]
+version = \"1.2.0\"
+
+[workspace.dependencies]
+core = { path = \"crates/core\" }
+
+[workspace.metadata.ws-manage]
+version-mode = \"lockstep\"
+",
+        );
+        write(ctx, "src/main.rs", "fn main() {}");
+        write(
+            ctx,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+        );
+        write(ctx, "crates/core/src/lib.rs", "");
+        write(
+            ctx,
+            "crates/cli/Cargo.toml",
+            "\
+[package]
+name = \"cli\"
+version = \"0.1.0\"
+publish = false
+
+[dev-dependencies]
+my-core = { path = \"../core\", package = \"core\" }
+",
+        );
+        write(ctx, "crates/cli/src/main.rs", "fn main() {}");
+        write(ctx, "crates/old/Cargo.toml", "this is not toml");
+
+        let ws = Workspace::load(&Path::new(&ctx.name).join("crates/cli/src")).unwrap();
+
+        assert_eq!(
+            ws.root_path,
+            fs::absolute_path(Path::new(&ctx.name)).unwrap()
+        );
+        assert_eq!(ws.packages.len(), 3);
+        assert_eq!(ws.settings.version_mode, Some(VersionMode::Lockstep));
+
+        let root = ws.root_package().unwrap();
+        assert_eq!(root.name, "demo");
+        assert_eq!(root.version, Some(String::from("1.2.0")));
+        assert!(root.is_bin() && !root.is_lib());
+        assert!(root.depends_on("core"));
+
+        let core = ws.package("core").unwrap();
+        assert_eq!(core.directory, Path::new("crates/core"));
+        assert!(core.is_lib() && !core.is_bin());
+
+        let cli = ws.package("cli").unwrap();
+        assert!(!cli.publish);
+        assert!(!cli.depends_on("core"));
+        assert_eq!(cli.dependencies[0].key, "my-core");
+        assert_eq!(cli.dependencies[0].package, "core");
+
+        let dependents: Vec<&str> = ws
+            .dependents("core")
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(dependents, vec!["demo", "cli"]);
+    }
+
+    #[test_context(TestFiles)]
+    #[test]
+    fn test_affected_packages(ctx: &mut TestFiles) {
+        write(
+            ctx,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"core\", \"cli\", \"app\", \"other\"]\n",
+        );
+        write(ctx, "core/Cargo.toml", "[package]\nname = \"core\"\n");
+        write(
+            ctx,
+            "cli/Cargo.toml",
+            "[package]\nname = \"cli\"\n[dependencies]\ncore = { path = \"../core\" }\n",
+        );
+        write(
+            ctx,
+            "app/Cargo.toml",
+            "[package]\nname = \"app\"\n[dev-dependencies]\ncli = { path = \"../cli\" }\n",
+        );
+        write(ctx, "other/Cargo.toml", "[package]\nname = \"other\"\n");
+        write(ctx, "core/src/lib.rs", "");
+
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&ctx.name)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        let ws = Workspace::load(Path::new(&ctx.name)).unwrap();
+        assert!(ws.affected_packages("HEAD").unwrap().is_empty());
+
+        write(ctx, "core/src/lib.rs", "pub fn changed() {}");
+        let affected: Vec<(&str, bool)> = ws
+            .affected_packages("HEAD")
+            .unwrap()
+            .into_iter()
+            .map(|(p, changed)| (p.name.as_str(), changed))
+            .collect();
+        assert_eq!(
+            affected,
+            vec![("core", true), ("cli", false), ("app", false)]
+        );
+
+        write(ctx, "other/README.md", "untracked");
+        let changed: Vec<&str> = ws
+            .changed_packages("HEAD")
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(changed, vec!["core", "other"]);
+    }
+}
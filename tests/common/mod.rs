@@ -0,0 +1,139 @@
+//! Helpers for the integration tests, which run the binary in temporary directories.
+
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use rand::{thread_rng, Rng};
+use test_context::TestContext;
+
+/// A temporary directory which is deleted after the test.
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestContext for TestDir {
+    fn setup() -> TestDir {
+        let path = std::env::temp_dir().join(format!(
+            "cargo_ws_manage_test_{}",
+            thread_rng().gen_range(0..1_000_000)
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+
+        TestDir { path }
+    }
+
+    fn teardown(self) {
+        if std::fs::remove_dir_all(self.path).is_err() {};
+    }
+}
+
+/// Creates the command running `cargo ws-manage` in `dir`. The user configuration
+/// is read from `config_home`, so that the one of the user running the tests is ignored.
+fn command(dir: &Path, args: &[&str], config_home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_cargo-ws-manage"));
+    command
+        .arg("ws-manage")
+        .args(args)
+        .current_dir(dir)
+        .env("XDG_CONFIG_HOME", config_home);
+    command
+}
+
+/// Directory without a user configuration.
+fn no_config_home() -> PathBuf {
+    std::env::temp_dir().join("cargo_ws_manage_no_config")
+}
+
+/// Runs `cargo ws-manage` with the given arguments in `dir`, without a terminal.
+pub fn ws_manage(dir: &Path, args: &[&str]) -> Output {
+    ws_manage_with_config(dir, args, &no_config_home())
+}
+
+/// Runs `cargo ws-manage` like [ws_manage], but with the user configuration
+/// file `cargo-ws-manage/config.toml` inside `config_home`.
+pub fn ws_manage_with_config(dir: &Path, args: &[&str], config_home: &Path) -> Output {
+    command(dir, args, config_home)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+/// Runs `cargo ws-manage` like [ws_manage], with additional environment variables.
+pub fn ws_manage_with_env(dir: &Path, args: &[&str], env: &[(&str, &str)]) -> Output {
+    command(dir, args, &no_config_home())
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+/// Runs `cargo ws-manage` like [ws_manage], but answers its questions with `input`.
+pub fn ws_manage_with_input(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = command(dir, args, &no_config_home())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Runs `cargo ws-manage` like [ws_manage] and panics if it fails.
+pub fn ws_manage_ok(dir: &Path, args: &[&str]) -> String {
+    let output = ws_manage(dir, args);
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(output.status.success(), "{:?} failed: {}", args, stdout);
+
+    stdout
+}
+
+/// Runs `cargo metadata` offline in `dir`, which fails if the workspace cannot be loaded.
+pub fn cargo_metadata(dir: &Path) -> serde_json::Value {
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo")))
+        .args(["metadata", "--offline", "--format-version", "1"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "cargo metadata failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Returns the package called `name` from the output of [cargo_metadata].
+pub fn package<'a>(metadata: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    metadata["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == name)
+        .unwrap_or_else(|| panic!("package {} not found", name))
+}
+
+/// Returns the directory of a package from the output of [cargo_metadata].
+pub fn package_dir(metadata: &serde_json::Value, name: &str) -> PathBuf {
+    let manifest_path = package(metadata, name)["manifest_path"].as_str().unwrap();
+    Path::new(manifest_path).parent().unwrap().to_path_buf()
+}
+
+/// Returns the names of the dependencies of a package from the output of [cargo_metadata].
+pub fn dependencies(metadata: &serde_json::Value, name: &str) -> Vec<String> {
+    package(metadata, name)["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["name"].as_str().unwrap().to_owned())
+        .collect()
+}
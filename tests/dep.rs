@@ -0,0 +1,113 @@
+//! Runs the 'dep add' command on a new workspace and checks the Cargo.toml of the crate.
+
+mod common;
+
+use common::{cargo_metadata, dependencies, ws_manage, ws_manage_ok, TestDir};
+use test_context::test_context;
+
+#[test_context(TestDir)]
+#[test]
+fn test_dep_add(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "a", "--member", "b:lib", "--yes",
+        ],
+    );
+    let root = ctx.path.join("demo");
+
+    ws_manage_ok(
+        &root,
+        &[
+            "dep",
+            "add",
+            "a",
+            "serde@1.0",
+            "--features",
+            "derive",
+            "--no-default-features",
+        ],
+    );
+    ws_manage_ok(&root, &["dep", "add", "a", "b", "--path", "b"]);
+    assert!(dependencies(&cargo_metadata(&root), "a").contains(&String::from("b")));
+    ws_manage_ok(
+        &root,
+        &[
+            "dep",
+            "add",
+            "a",
+            "log",
+            "--git",
+            "https://github.com/rust-lang/log",
+            "--tag",
+            "0.4.17",
+            "--rename",
+            "logging",
+            "--dev",
+        ],
+    );
+    ws_manage_ok(
+        &root,
+        &[
+            "dep",
+            "add",
+            "a",
+            "libc@0.2",
+            "--optional",
+            "--target",
+            "cfg(unix)",
+        ],
+    );
+
+    let a = std::fs::read_to_string(root.join("a/Cargo.toml")).unwrap();
+    assert!(a.contains(
+        "[dependencies]\nserde = { version = \"1.0\", default-features = false, features = [\"derive\"] }\nb = { path = \"../b\" }\n"
+    ));
+    assert!(a.contains(
+        "[dev-dependencies]\nlogging = { git = \"https://github.com/rust-lang/log\", tag = \"0.4.17\", package = \"log\" }\n"
+    ));
+    assert!(a.contains(
+        "[target.\"cfg(unix)\".dependencies]\nlibc = { version = \"0.2\", optional = true }\n"
+    ));
+
+    // Invalid or duplicate dependencies are rejected without changing anything.
+    for args in [
+        &["dep", "add", "a", "serde@1.0"][..],
+        &["dep", "add", "a", "rand@latest"],
+        &["dep", "add", "a", "rand"],
+        &["dep", "add", "a", "c", "--path", "c"],
+        &["dep", "add", "c", "rand@0.8"],
+        &["dep", "add", "a", "rand@0.8", "--branch", "main"],
+    ] {
+        assert!(!ws_manage(&root, args).status.success(), "{:?}", args);
+    }
+    assert_eq!(
+        std::fs::read_to_string(root.join("a/Cargo.toml")).unwrap(),
+        a
+    );
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_dep_add_inherited(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &["new", "demo", "--root", "none", "--member", "a", "--yes"],
+    );
+    let root = ctx.path.join("demo");
+    ws_manage_ok(&root, &["deps", "add", "rand@0.8"]);
+
+    ws_manage_ok(
+        &root,
+        &["dep", "add", "a", "rand", "--features", "small_rng"],
+    );
+    let a = std::fs::read_to_string(root.join("a/Cargo.toml")).unwrap();
+    assert!(a.contains("rand = { workspace = true, features = [\"small_rng\"] }"));
+
+    assert!(!ws_manage(
+        &root,
+        &["dep", "add", "a", "rand", "--rename", "random", "--dev"]
+    )
+    .status
+    .success());
+}
@@ -0,0 +1,85 @@
+//! Runs the 'deps' command on a new workspace and checks the changed Cargo.toml files.
+
+mod common;
+
+use common::{ws_manage, ws_manage_ok, TestDir};
+use test_context::test_context;
+
+#[test_context(TestDir)]
+#[test]
+fn test_deps_hoist_and_add(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "a", "--member", "b", "--yes",
+        ],
+    );
+    let root = ctx.path.join("demo");
+    let append = |file: &str, content: &str| {
+        let path = root.join(file);
+        let old = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, old + content).unwrap();
+    };
+    append(
+        "a/Cargo.toml",
+        "\n[dependencies]\nserde = { version = \"1.0.100\", features = [\"derive\"] }\n",
+    );
+    append("b/Cargo.toml", "\n[dependencies]\nserde = \"1.0.140\"\n");
+
+    let stdout = ws_manage_ok(&root, &["deps", "hoist"]);
+    assert!(stdout.contains("serde = \"1.0.140\" (a, b)"));
+
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("[workspace.dependencies]\nserde = \"1.0.140\"\n"));
+    let a = std::fs::read_to_string(root.join("a/Cargo.toml")).unwrap();
+    assert!(a.contains("serde = { workspace = true, features = [\"derive\"] }"));
+    let b = std::fs::read_to_string(root.join("b/Cargo.toml")).unwrap();
+    assert!(b.contains("serde = { workspace = true }"));
+
+    // Without a requirement, only dependencies already in [workspace.dependencies] can be added.
+    assert!(!ws_manage(&root, &["deps", "add", "log", "--members", "a"])
+        .status
+        .success());
+    let output = ws_manage(&root, &["deps", "add", "log@latest", "--members", "a"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("latest is not a valid version requirement"));
+
+    ws_manage_ok(
+        &root,
+        &["deps", "add", "log@0.4", "--members", "a,b", "--dev"],
+    );
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("log = \"0.4\""));
+    for member in ["a", "b"] {
+        let content = std::fs::read_to_string(root.join(member).join("Cargo.toml")).unwrap();
+        assert!(content.contains("[dev-dependencies]\nlog = { workspace = true }\n"));
+    }
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_deps_check(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "a", "--member", "b", "--yes",
+        ],
+    );
+    let root = ctx.path.join("demo");
+    for (member, requirement) in [("a", "1.0.100"), ("b", "1.0.140")] {
+        let path = root.join(member).join("Cargo.toml");
+        let old = std::fs::read_to_string(&path).unwrap();
+        let new = format!("{}\n[dependencies]\nserde = \"{}\"\n", old, requirement);
+        std::fs::write(&path, new).unwrap();
+    }
+
+    let output = ws_manage(&root, &["deps", "check"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Mismatched requirements of serde: \"1.0.100\" (a), \"1.0.140\" (b)"));
+
+    ws_manage_ok(&root, &["deps", "check", "--fix"]);
+    let a = std::fs::read_to_string(root.join("a/Cargo.toml")).unwrap();
+    assert!(a.contains("serde = \"1.0.140\""));
+}
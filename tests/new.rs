@@ -0,0 +1,600 @@
+//! Runs the 'new' command non-interactively and checks that cargo can load the result.
+
+mod common;
+
+use common::{
+    cargo_metadata, dependencies, package, package_dir, ws_manage, ws_manage_ok,
+    ws_manage_with_config, ws_manage_with_input, TestDir,
+};
+use test_context::test_context;
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_members(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "bin",
+            "--member",
+            "core:core-dir:dep",
+            "--member",
+            "cli:cli-dir:bin",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let metadata = cargo_metadata(&root);
+
+    assert_eq!(package_dir(&metadata, "demo"), root);
+    assert_eq!(package_dir(&metadata, "core"), root.join("core-dir"));
+    assert_eq!(package_dir(&metadata, "cli"), root.join("cli-dir"));
+    assert_eq!(dependencies(&metadata, "demo"), vec!["core"]);
+    assert!(root.join("cli-dir/src/main.rs").is_file());
+    assert!(root.join("core-dir/src/lib.rs").is_file());
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_without_root(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new", "demo", "demo-dir", "--root", "none", "--member", "a", "--member", "b:b-dir",
+        ],
+    );
+
+    let root = ctx.path.join("demo-dir").canonicalize().unwrap();
+    let metadata = cargo_metadata(&root);
+
+    assert_eq!(metadata["packages"].as_array().unwrap().len(), 2);
+    assert_eq!(package_dir(&metadata, "a"), root.join("a"));
+    assert_eq!(package_dir(&metadata, "b"), root.join("b-dir"));
+
+    // A virtual manifest has no edition, so the resolver has to be set explicitly.
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("resolver = \"2\""));
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_workspace_package(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "lib",
+            "--member",
+            "core",
+            "--workspace-package",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("[workspace.package]\nversion = \"0.1.0\"\nedition = \"2021\"\n"));
+
+    let member = std::fs::read_to_string(root.join("core/Cargo.toml")).unwrap();
+    assert!(member.contains("version.workspace = true\nedition.workspace = true\n"));
+
+    let metadata = cargo_metadata(&root);
+    for name in ["demo", "core"] {
+        assert_eq!(common::package(&metadata, name)["version"], "0.1.0");
+        assert_eq!(common::package(&metadata, name)["edition"], "2021");
+    }
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_member_dependencies(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--members-dir",
+            "crates",
+            "--member",
+            "core",
+            "--member",
+            "model:uses=core",
+            "--member",
+            "api:api-dir:uses=model",
+            "--member",
+            "cli:bin:uses=api,core",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let metadata = cargo_metadata(&root);
+
+    assert_eq!(dependencies(&metadata, "model"), vec!["core"]);
+    assert_eq!(dependencies(&metadata, "api"), vec!["model"]);
+    assert_eq!(dependencies(&metadata, "cli"), vec!["api", "core"]);
+
+    let api = std::fs::read_to_string(root.join("api-dir/Cargo.toml")).unwrap();
+    assert!(api.contains("model = { path = \"../crates/model\" }"));
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_dependency_cycle(ctx: &mut TestDir) {
+    let output = ws_manage(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "a:uses=c", "--member", "b:uses=a",
+            "--member", "c:uses=b", "--yes",
+        ],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("dependency cycle: a -> c -> b -> a"));
+    assert!(!ctx.path.join("demo").exists());
+
+    // Binary crates cannot be depended on.
+    let output = ws_manage(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "a:bin", "--member", "b:uses=a", "--yes",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(!ctx.path.join("demo").exists());
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_from_blueprint(ctx: &mut TestDir) {
+    std::fs::write(
+        ctx.path.join("blueprint.toml"),
+        "\
+[metadata]
+version = \"0.3.0\"
+
+[root]
+kind = \"bin\"
+
+[[members]]
+name = \"engine\"
+directory = \"engine-dir\"
+as_dependency = true
+
+[[members]]
+name = \"plugin\"
+directory = \"plugin-dir\"
+dependencies = [\"engine\"]
+",
+    )
+    .unwrap();
+
+    ws_manage_ok(&ctx.path, &["new", "demo", "--from", "blueprint.toml"]);
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let metadata = cargo_metadata(&root);
+
+    assert_eq!(package_dir(&metadata, "engine"), root.join("engine-dir"));
+    assert_eq!(package_dir(&metadata, "plugin"), root.join("plugin-dir"));
+    assert_eq!(dependencies(&metadata, "demo"), vec!["engine"]);
+    assert_eq!(dependencies(&metadata, "plugin"), vec!["engine"]);
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_needs_terminal(ctx: &mut TestDir) {
+    let output = ws_manage(&ctx.path, &["new", "demo"]);
+
+    assert!(!output.status.success());
+    assert!(!ctx.path.join("demo").exists());
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_members_dir(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "lib",
+            "--members-dir",
+            "crates",
+            "--glob-members",
+            "--member",
+            "core:dep",
+            "--member",
+            "cli:bin",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("members = [\"crates/*\"]"));
+    assert!(manifest.contains("[workspace.metadata.ws-manage]\nmembers-dir = \"crates\""));
+
+    let metadata = cargo_metadata(&root);
+    assert_eq!(package_dir(&metadata, "core"), root.join("crates/core"));
+    assert_eq!(package_dir(&metadata, "cli"), root.join("crates/cli"));
+    assert_eq!(dependencies(&metadata, "demo"), vec!["core"]);
+
+    // New crates are put into the members directory and matched by the glob pattern.
+    let output = ws_manage_with_input(&root, &["add", "util"], "\n\n\n\n");
+    assert!(output.status.success());
+
+    let metadata = cargo_metadata(&root);
+    assert_eq!(package_dir(&metadata, "util"), root.join("crates/util"));
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("members = [\"crates/*\"]"));
+    assert!(manifest.contains("util = { path = \"crates/util\" }"));
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_explicit_members(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--members-dir",
+            "crates",
+            "--member",
+            "core",
+            "--member",
+            "cli:bin",
+            "--yes",
+        ],
+    );
+
+    // Without --glob-members, every member is listed on its own.
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("\"crates/core\""));
+    assert!(manifest.contains("\"crates/cli\""));
+    assert!(!manifest.contains("crates/*"));
+
+    let output = ws_manage_with_input(&root, &["add", "util"], "\n\n\n\n");
+    assert!(output.status.success());
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("\"crates/util\""));
+    assert_eq!(
+        package_dir(&cargo_metadata(&root), "util"),
+        root.join("crates/util")
+    );
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_templates(ctx: &mut TestDir) {
+    let template = ctx.path.join("templates/service");
+    std::fs::create_dir_all(template.join("src")).unwrap();
+    std::fs::write(
+        template.join("src/main.rs"),
+        "// {{crate_name}} of {{project_name}}\nfn main() {}\n",
+    )
+    .unwrap();
+    std::fs::write(template.join("README.md"), "# {{crate_name}}\n").unwrap();
+    std::fs::write(template.join("Cargo.toml"), "[features]\nmetrics = []\n").unwrap();
+
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--template-dir",
+            "templates",
+            "--member",
+            "app:template=bin-lib",
+            "--member",
+            "ffi:template=cdylib",
+            "--member",
+            "perf:template=bench",
+            "--member",
+            "api:template=service:uses=app",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let metadata = cargo_metadata(&root);
+    let kinds = |name: &str| -> Vec<String> {
+        common::package(&metadata, name)["targets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["kind"][0].as_str().unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(kinds("app"), vec!["lib", "bin"]);
+    assert_eq!(kinds("ffi"), vec!["cdylib"]);
+    assert_eq!(kinds("perf"), vec!["lib", "bench"]);
+    assert_eq!(kinds("api"), vec!["bin"]);
+
+    assert_eq!(
+        std::fs::read_to_string(root.join("app/src/main.rs")).unwrap(),
+        "fn main() {\n    app::run();\n}\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("api/src/main.rs")).unwrap(),
+        "// api of demo\nfn main() {}\n"
+    );
+    assert!(root.join("api/README.md").is_file());
+    let api = std::fs::read_to_string(root.join("api/Cargo.toml")).unwrap();
+    assert!(
+        api.contains("\n[features]\nmetrics = []\n\n[dependencies]\napp = { path = \"../app\" }\n")
+    );
+
+    // The template directory is stored relative to the workspace and used by 'add'.
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("template-dir = \"../templates\""));
+    ws_manage_ok(&root, &["add", "worker", "worker", "--template", "service"]);
+    assert!(root.join("worker/README.md").is_file());
+
+    ws_manage_ok(
+        &root,
+        &["add", "derive", "derive", "--template", "proc-macro"],
+    );
+    let derive = std::fs::read_to_string(root.join("derive/Cargo.toml")).unwrap();
+    assert!(derive.contains("[lib]\nproc-macro = true\n"));
+    assert!(derive.contains("syn = \"2.0\""));
+
+    assert!(
+        !ws_manage(&root, &["add", "other", "other", "--template", "unknown"])
+            .status
+            .success()
+    );
+    assert!(!root.join("other").exists());
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_defaults(ctx: &mut TestDir) {
+    let config_home = ctx.path.join("config");
+    std::fs::create_dir_all(config_home.join("cargo-ws-manage")).unwrap();
+    std::fs::write(
+        config_home.join("cargo-ws-manage/config.toml"),
+        "edition = \"2018\"\nlicense = \"MIT\"\nauthors = [\"Jane Doe\"]\nmembers-dir = \"crates\"\ncrate-kind = \"bin\"\n",
+    )
+    .unwrap();
+
+    // Flags take precedence over the user configuration.
+    let output = ws_manage_with_config(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--member",
+            "core:lib",
+            "--member",
+            "tool",
+            "--license",
+            "Apache-2.0",
+            "--yes",
+        ],
+        &config_home,
+    );
+    assert!(output.status.success());
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    assert!(root.join("crates/core/src/lib.rs").is_file());
+    assert!(root.join("crates/tool/src/main.rs").is_file());
+    let tool = std::fs::read_to_string(root.join("crates/tool/Cargo.toml")).unwrap();
+    assert!(tool.contains("edition = \"2018\""));
+    assert!(tool.contains("authors = [\"Jane Doe\"]"));
+    assert!(tool.contains("license = \"Apache-2.0\""));
+
+    // The defaults of the workspace take precedence over the user configuration.
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        root.join("Cargo.toml"),
+        manifest.replace(
+            "members-dir = \"crates\"",
+            "members-dir = \"crates\"\nedition = \"2021\"\nversion = \"0.2.0\"",
+        ),
+    )
+    .unwrap();
+    let output = ws_manage_with_config(
+        &root,
+        &["add", "util", "crates/util", "--template", "lib"],
+        &config_home,
+    );
+    assert!(output.status.success());
+
+    let util = std::fs::read_to_string(root.join("crates/util/Cargo.toml")).unwrap();
+    assert!(util.contains("version = \"0.2.0\""));
+    assert!(util.contains("edition = \"2021\""));
+    assert!(util.contains("license = \"MIT\""));
+    cargo_metadata(&root);
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_derive_crate(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "bin",
+            "--members-dir",
+            "crates",
+            "--member",
+            "model:dep:derive",
+            "--member",
+            "macros:proc-macro",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    let derive = std::fs::read_to_string(root.join("crates/model-derive/Cargo.toml")).unwrap();
+    assert!(derive.contains("[lib]\nproc-macro = true\n"));
+    let macros = std::fs::read_to_string(root.join("crates/macros/Cargo.toml")).unwrap();
+    assert!(macros.contains("[lib]\nproc-macro = true\n"));
+
+    let lib = std::fs::read_to_string(root.join("crates/model/src/lib.rs")).unwrap();
+    assert!(lib.starts_with("pub use model_derive::*;\n"));
+
+    let metadata = cargo_metadata(&root);
+    assert_eq!(dependencies(&metadata, "model"), vec!["model-derive"]);
+
+    // 'add' creates the pair as well.
+    let output = ws_manage_with_input(
+        &root,
+        &[
+            "add",
+            "shapes",
+            "crates/shapes",
+            "--kind",
+            "lib",
+            "--derive",
+        ],
+        "n\n",
+    );
+    assert!(output.status.success());
+    let metadata = cargo_metadata(&root);
+    assert_eq!(
+        package_dir(&metadata, "shapes-derive"),
+        root.join("crates/shapes-derive")
+    );
+    assert_eq!(dependencies(&metadata, "shapes"), vec!["shapes-derive"]);
+
+    // Only library crates can have a derive crate.
+    assert!(!ws_manage(
+        &ctx.path,
+        &[
+            "new",
+            "other",
+            "--root",
+            "none",
+            "--member",
+            "cli:bin:derive",
+            "--yes"
+        ]
+    )
+    .status
+    .success());
+}
+
+/// Returns the kinds and names of the targets of a package, sorted.
+fn targets(metadata: &serde_json::Value, name: &str) -> Vec<String> {
+    let mut targets: Vec<String> = package(metadata, name)["targets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            format!(
+                "{} {}",
+                t["kind"][0].as_str().unwrap(),
+                t["name"].as_str().unwrap()
+            )
+        })
+        .collect();
+    targets.sort();
+    targets
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_new_with_targets(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--member",
+            "app:lib:bin:bins=admin,import=tools/import.rs:examples=demo:benches=speed",
+            "--yes",
+        ],
+    );
+
+    let root = ctx.path.join("demo").canonicalize().unwrap();
+    for file in [
+        "src/lib.rs",
+        "src/main.rs",
+        "src/bin/admin.rs",
+        "tools/import.rs",
+        "examples/demo.rs",
+        "benches/speed.rs",
+    ] {
+        assert!(root.join("app").join(file).is_file(), "{}", file);
+    }
+
+    // Only targets cargo can't find by itself are written to the manifest.
+    let manifest = std::fs::read_to_string(root.join("app/Cargo.toml")).unwrap();
+    assert!(manifest.contains("[[bin]]\nname = \"import\"\npath = \"tools/import.rs\"\n"));
+    assert!(manifest.contains("[[bench]]\nname = \"speed\"\nharness = false\n"));
+    assert!(!manifest.contains("admin"));
+
+    let metadata = cargo_metadata(&root);
+    assert_eq!(
+        targets(&metadata, "app"),
+        vec![
+            "bench speed",
+            "bin admin",
+            "bin app",
+            "bin import",
+            "example demo",
+            "lib app"
+        ]
+    );
+
+    // 'add' takes the targets as flags.
+    let output = ws_manage_with_input(
+        &root,
+        &[
+            "add",
+            "tool",
+            "crates/tool",
+            "--kind",
+            "bin",
+            "--lib",
+            "--bin",
+            "extra",
+            "--test",
+            "smoke",
+        ],
+        "n\n",
+    );
+    assert!(output.status.success());
+    let metadata = cargo_metadata(&root);
+    assert_eq!(
+        targets(&metadata, "tool"),
+        vec!["bin extra", "bin tool", "lib tool", "test smoke"]
+    );
+
+    // Two binaries can't have the same name.
+    assert!(!ws_manage(
+        &ctx.path,
+        &[
+            "new",
+            "other",
+            "--root",
+            "none",
+            "--member",
+            "cli:bin:bins=tool,tool=src/tool.rs",
+            "--yes"
+        ]
+    )
+    .status
+    .success());
+}
@@ -0,0 +1,126 @@
+//! Runs the 'publish' command on a new workspace, with a stand-in for `cargo publish`.
+
+mod common;
+
+use std::path::Path;
+
+use common::{ws_manage_ok, ws_manage_with_env, TestDir};
+use test_context::test_context;
+
+/// Creates a workspace with the members core, model (using core) and cli (using model).
+fn create_workspace(dir: &Path) -> std::path::PathBuf {
+    ws_manage_ok(
+        dir,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--members-dir",
+            "crates",
+            "--member",
+            "cli:bin:uses=model",
+            "--member",
+            "model:uses=core",
+            "--member",
+            "core",
+            "--yes",
+        ],
+    );
+    dir.join("demo")
+}
+
+/// Reads the Cargo.toml files of the workspace.
+fn manifests(root: &Path) -> Vec<String> {
+    [
+        "Cargo.toml",
+        "crates/cli/Cargo.toml",
+        "crates/model/Cargo.toml",
+        "crates/core/Cargo.toml",
+    ]
+    .iter()
+    .map(|f| std::fs::read_to_string(root.join(f)).unwrap())
+    .collect()
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_publish_dry_run(ctx: &mut TestDir) {
+    let root = create_workspace(&ctx.path);
+    let before = manifests(&root);
+
+    let stdout = ws_manage_ok(&root, &["publish", "--dry-run"]);
+    let order: Vec<&str> = stdout
+        .lines()
+        .skip_while(|l| !l.starts_with("Crates will be published"))
+        .skip(1)
+        .take_while(|l| l.starts_with("    "))
+        .map(str::trim)
+        .collect();
+    assert_eq!(order, vec!["core 0.1.0", "model 0.1.0", "cli 0.1.0"]);
+    assert!(stdout.contains("crates/model/Cargo.toml: core = \"0.1.0\""));
+    assert!(stdout.contains("crates/cli/Cargo.toml: model = \"0.1.0\""));
+
+    assert_eq!(manifests(&root), before);
+}
+
+#[cfg(unix)]
+#[test_context(TestDir)]
+#[test]
+fn test_publish_to_local_registry(ctx: &mut TestDir) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = create_workspace(&ctx.path);
+    // cli inherits its dependency on model from the workspace.
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        root.join("Cargo.toml"),
+        manifest + "\n[workspace.dependencies]\nmodel = { path = \"crates/model\" }\n",
+    )
+    .unwrap();
+    let cli = std::fs::read_to_string(root.join("crates/cli/Cargo.toml")).unwrap();
+    std::fs::write(
+        root.join("crates/cli/Cargo.toml"),
+        cli.replace("{ path = \"../model\" }", "{ workspace = true }"),
+    )
+    .unwrap();
+
+    // The stand-in for cargo copies the Cargo.toml of every published crate into a
+    // registry directory and records the order and the arguments in a log file.
+    let bin = ctx.path.join("bin");
+    let registry = ctx.path.join("registry");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::create_dir_all(&registry).unwrap();
+    let cargo = bin.join("cargo");
+    std::fs::write(
+        &cargo,
+        "#!/bin/sh\nname=$(basename \"$PWD\")\ncp Cargo.toml \"$REGISTRY/$name.toml\"\necho \"$name $*\" >> \"$REGISTRY/log\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+    let output = ws_manage_with_env(
+        &root,
+        &["publish", "--no-verify"],
+        &[
+            ("PATH", path.as_str()),
+            ("REGISTRY", registry.to_str().unwrap()),
+        ],
+    );
+    assert!(output.status.success());
+
+    // The manifests were updated, so --allow-dirty is passed even though it wasn't given.
+    let log = std::fs::read_to_string(registry.join("log")).unwrap();
+    assert_eq!(
+        log,
+        "core publish --allow-dirty --no-verify\n\
+         model publish --allow-dirty --no-verify\n\
+         cli publish --allow-dirty --no-verify\n"
+    );
+
+    let model = std::fs::read_to_string(registry.join("model.toml")).unwrap();
+    assert!(model.contains("core = { path = \"../core\", version = \"0.1.0\" }"));
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("model = { path = \"crates/model\", version = \"0.1.0\" }"));
+}
@@ -0,0 +1,84 @@
+//! Runs the 'remove' command on a new workspace and checks that cargo can still load it.
+
+mod common;
+
+use common::{cargo_metadata, ws_manage, ws_manage_ok, TestDir};
+use test_context::test_context;
+
+#[test_context(TestDir)]
+#[test]
+fn test_remove_inherited_dependency(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new",
+            "demo",
+            "--root",
+            "none",
+            "--members-dir",
+            "crates",
+            "--member",
+            "foo",
+            "--member",
+            "bar",
+            "--yes",
+        ],
+    );
+    let root = ctx.path.join("demo");
+    let write = |file: &str, content: &str| std::fs::write(root.join(file), content).unwrap();
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    write(
+        "Cargo.toml",
+        &(manifest + "\n[workspace.dependencies]\nfoo = { path = \"crates/foo\" }\n"),
+    );
+    write(
+        "crates/bar/Cargo.toml",
+        "[package]\nname = \"bar\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nfoo = { workspace = true }\n",
+    );
+    write("crates/bar/src/lib.rs", "pub fn g() {\n    foo::f();\n}\n");
+    write("crates/foo/src/lib.rs", "pub fn f() {}\n");
+    cargo_metadata(&root);
+
+    // bar uses foo through [workspace.dependencies].
+    let output = ws_manage(&root, &["remove", "foo", "--delete"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("crates/bar/src/lib.rs"));
+    assert!(root.join("crates/foo").exists());
+
+    ws_manage_ok(&root, &["remove", "foo", "--delete", "--force"]);
+    assert!(!root.join("crates/foo").exists());
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(!manifest.contains("foo"));
+    let bar = std::fs::read_to_string(root.join("crates/bar/Cargo.toml")).unwrap();
+    assert!(!bar.contains("foo"));
+    cargo_metadata(&root);
+}
+
+#[test_context(TestDir)]
+#[test]
+fn test_remove_dependency_with_features(ctx: &mut TestDir) {
+    ws_manage_ok(
+        &ctx.path,
+        &[
+            "new", "demo", "--root", "none", "--member", "foo", "--member", "bar", "--yes",
+        ],
+    );
+    let root = ctx.path.join("demo");
+    let write = |file: &str, content: &str| std::fs::write(root.join(file), content).unwrap();
+    write(
+        "foo/Cargo.toml",
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[features]\nstd = []\n",
+    );
+    write(
+        "bar/Cargo.toml",
+        "[package]\nname = \"bar\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nfoo = { path = \"../foo\", optional = true }\n\n[features]\nstd = [\"foo?/std\"]\nfull = [\"std\", \"dep:foo\"]\n",
+    );
+    cargo_metadata(&root);
+
+    let stdout = ws_manage_ok(&root, &["remove", "foo", "--delete"]);
+    assert!(stdout.contains("removed \"foo?/std\" from the feature std"));
+    assert!(stdout.contains("removed \"dep:foo\" from the feature full"));
+    let bar = std::fs::read_to_string(root.join("bar/Cargo.toml")).unwrap();
+    assert!(bar.ends_with("[features]\nstd = []\nfull = [\"std\"]\n"));
+    cargo_metadata(&root);
+}